@@ -0,0 +1,24 @@
+#![no_main]
+
+use fraux_rs::{ParseOptions, Parser, Tokenizer};
+use libfuzzer_sys::fuzz_target;
+
+// 任何输入都不能 panic, 解析成功的数据编码后应当得到相同的数据
+fuzz_target!(|src: &[u8]| {
+    let parsed = fraux_rs::parse(src);
+    let _ = fraux_rs::parse_with_options(src, &ParseOptions::strict());
+    let _ = fraux_rs::parse_with_spans(src);
+    let _ = fraux_rs::parse_all(src);
+    let _ = Tokenizer::new(src).count();
+
+    let mut p = Parser::new();
+    for chunk in src.chunks(7) {
+        let _ = p.push(chunk);
+    }
+    let _ = p.finish();
+
+    if let Ok(data) = parsed {
+        let encoded = fraux_rs::stringify(&data).unwrap();
+        assert_eq!(fraux_rs::parse(&encoded).unwrap(), data);
+    }
+});
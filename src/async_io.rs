@@ -0,0 +1,84 @@
+use crate::{BData, ParseErr, Parser, READER_BUF_SIZE};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// 从 `AsyncRead` 中解析数据, 数据不完整时等待更多的输入.
+///
+/// 与 `parse_reader` 相同, 数据结束后不会再读取, 已经读入缓冲区的多余字节返回
+/// `ParseErr::TrailingData`.
+pub async fn parse_async<R: AsyncRead + Unpin>(mut r: R) -> Result<BData, ParseErr> {
+    let mut parser = Parser::new();
+    let mut buf = [0; READER_BUF_SIZE];
+    let mut total = 0;
+    loop {
+        let n = r.read(&mut buf).await.map_err(ParseErr::IoError)?;
+        if n == 0 {
+            parser.finish()?;
+            return Err(ParseErr::UnexpectedEof {
+                expected: "value",
+                offset: 0,
+            });
+        }
+        total += n;
+        if let Some((data, consumed)) = parser.push(&buf[..n])? {
+            let offset = total - n + consumed;
+            if consumed < n {
+                return Err(ParseErr::TrailingData { offset });
+            }
+            return Ok(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_async;
+    use crate::ParseErr;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn parse_async_test() {
+        let src = b"d4:infod6:lengthi1024e4:name5:a.txte4:listl3:abci-7eee".to_vec();
+        let expected = crate::parse(&src).unwrap();
+
+        let (mut tx, rx) = tokio::io::duplex(3);
+        let chunks = src.clone();
+        let writer = tokio::spawn(async move {
+            for chunk in chunks.chunks(3) {
+                tx.write_all(chunk).await.unwrap();
+            }
+        });
+        let data = parse_async(rx).await.expect("parse_async failed");
+        writer.await.unwrap();
+        assert_eq!(data, expected);
+    }
+
+    #[tokio::test]
+    async fn parse_async_large_test() {
+        // 跨越多次读取的长字符串应当在线性时间内完成
+        let data = bdata!({"a" => vec![7u8; 16 << 20], "b" => [1, vec![9u8; 3 << 20]]});
+        let src = crate::stringify(&data).unwrap();
+        assert_eq!(parse_async(&src[..]).await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn parse_async_eof_test() {
+        let (mut tx, rx) = tokio::io::duplex(64);
+        tx.write_all(b"d1:al").await.unwrap();
+        drop(tx);
+        let v = parse_async(rx).await;
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 5, .. })));
+    }
+
+    #[tokio::test]
+    async fn parse_async_trailing_test() {
+        let v = parse_async(&b"i1ejunk"[..]).await;
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset: 3 })));
+
+        // 连接没有关闭时数据结束后立即返回, 不等待更多的输入
+        let (mut tx, rx) = tokio::io::duplex(64);
+        tx.write_all(b"l1:ae").await.unwrap();
+        let v = parse_async(rx).await.unwrap();
+        assert_eq!(v, bdata!(["a"]));
+        drop(tx);
+    }
+}
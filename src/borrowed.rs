@@ -0,0 +1,198 @@
+use crate::map::{self, Map};
+use crate::{BData, DebugBytes, DebugDict, Node, ParseErr, ParseOptions, Token};
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+/// 借用输入的数据, 字符串与字典的键直接指向解析时的输入, 不会复制.
+///
+/// 由 `parse_borrowed` 产生, 可以通过 `to_owned` 转换为 `BData`
+#[derive(Eq, PartialEq, Clone)]
+pub enum BDataRef<'a> {
+    BString(&'a [u8]),
+    Number(i64),
+    /// 超出 `i64` 范围的整数, 参见 `BData::BigNumber`
+    BigNumber(&'a [u8]),
+    List(Vec<BDataRef<'a>>),
+    Dict(Map<&'a [u8], BDataRef<'a>>),
+}
+
+impl<'a> BDataRef<'a> {
+    /// 字符串的原始字节, 其他类型返回 `None`
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            BDataRef::BString(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// 字符串是合法的 UTF-8 时返回 `&str`, 否则返回 `None`
+    pub fn as_str(&self) -> Option<&'a str> {
+        self.as_bytes().and_then(|s| core::str::from_utf8(s).ok())
+    }
+
+    pub fn as_number(&self) -> Option<i64> {
+        match self {
+            BDataRef::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[BDataRef<'a>]> {
+        match self {
+            BDataRef::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_dict(&self) -> Option<&Map<&'a [u8], BDataRef<'a>>> {
+        match self {
+            BDataRef::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// 复制借用的字节, 转换为 `BData`
+    pub fn to_owned(&self) -> BData {
+        match self {
+            BDataRef::BString(s) => BData::BString(s.to_vec()),
+            BDataRef::Number(n) => BData::Number(*n),
+            BDataRef::BigNumber(n) => BData::BigNumber(n.to_vec()),
+            BDataRef::List(list) => BData::List(list.iter().map(|v| v.to_owned()).collect()),
+            BDataRef::Dict(dict) => BData::Dict(
+                dict.iter()
+                    .map(|(k, v)| (k.to_vec(), v.to_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// 与 `BData` 的 `Debug` 格式相同
+impl fmt::Debug for BDataRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BDataRef::BString(s) => f.debug_tuple("BString").field(&DebugBytes(s)).finish(),
+            BDataRef::Number(n) => f.debug_tuple("Number").field(n).finish(),
+            BDataRef::BigNumber(n) => f.debug_tuple("BigNumber").field(&DebugBytes(n)).finish(),
+            BDataRef::List(list) => f.debug_tuple("List").field(list).finish(),
+            BDataRef::Dict(dict) => f.debug_tuple("Dict").field(&DebugDict(dict)).finish(),
+        }
+    }
+}
+
+/// 字典按键的顺序计算, 与 `Eq` 一致
+impl Hash for BDataRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            BDataRef::BString(s) | BDataRef::BigNumber(s) => s.hash(state),
+            BDataRef::Number(n) => n.hash(state),
+            BDataRef::List(list) => list.hash(state),
+            BDataRef::Dict(dict) => {
+                dict.len().hash(state);
+                map::sorted(dict).for_each(|entry| entry.hash(state));
+            }
+        }
+    }
+}
+
+impl From<&BDataRef<'_>> for BData {
+    fn from(data: &BDataRef<'_>) -> BData {
+        data.to_owned()
+    }
+}
+
+impl<'a> Node<'a> for BDataRef<'a> {
+    type Key = &'a [u8];
+
+    fn leaf(token: Token<'a>) -> BDataRef<'a> {
+        match token {
+            Token::Int(n) => BDataRef::Number(n),
+            Token::BigInt(n) => BDataRef::BigNumber(n),
+            Token::Bytes(b) => BDataRef::BString(b),
+            _ => unreachable!("container token is not a leaf"),
+        }
+    }
+
+    fn list(list: Vec<BDataRef<'a>>) -> BDataRef<'a> {
+        BDataRef::List(list)
+    }
+
+    fn dict(dict: Map<&'a [u8], BDataRef<'a>>) -> BDataRef<'a> {
+        BDataRef::Dict(dict)
+    }
+
+    fn into_key(self) -> Option<&'a [u8]> {
+        match self {
+            BDataRef::BString(k) => Some(k),
+            _ => None,
+        }
+    }
+}
+
+/// 与 `parse` 相同, 但字符串借用 `src` 而不复制.
+///
+/// 只需要读取大文件中的少数字段时可以减少内存分配:
+///
+/// ```
+/// let src = b"d4:name5:a.txt6:lengthi5ee";
+/// let data = fraux_rs::parse_borrowed(src).unwrap();
+/// let name = data.as_dict().unwrap()[&b"name"[..]].as_str();
+/// assert_eq!(name, Some("a.txt"));
+/// assert_eq!(data.to_owned(), fraux_rs::parse(src).unwrap());
+/// ```
+pub fn parse_borrowed(src: &[u8]) -> Result<BDataRef<'_>, ParseErr> {
+    crate::parse_complete_with(src, &ParseOptions::default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::BDataRef;
+    use crate::ParseErr;
+
+    #[test]
+    fn parse_borrowed_test() {
+        let src = b"d4:infod6:pieces3:\x00\x01\xff4:name1:ae4:listli-1e0:leee";
+        let data = crate::parse_borrowed(src).unwrap();
+        assert_eq!(data.to_owned(), crate::parse(src).unwrap());
+
+        // 字符串指向输入本身
+        let info = data.as_dict().unwrap()[&b"info"[..]].as_dict().unwrap();
+        let pieces = info[&b"pieces"[..]].as_bytes().unwrap();
+        assert_eq!(pieces, b"\x00\x01\xff");
+        assert_eq!(pieces.as_ptr(), src[18..].as_ptr());
+        let (key, _) = info.get_key_value(&b"pieces"[..]).unwrap();
+        assert_eq!(key.as_ptr(), src[10..].as_ptr());
+
+        let list = data.as_dict().unwrap()[&b"list"[..]].as_list().unwrap();
+        assert_eq!(
+            list,
+            &[
+                BDataRef::Number(-1),
+                BDataRef::BString(b""),
+                BDataRef::List(vec![])
+            ][..]
+        );
+    }
+
+    #[test]
+    fn parse_borrowed_err_test() {
+        let cases: [&[u8]; 5] = [
+            b"d1:ai1e1:ai2ee",
+            b"di1ei2ee",
+            b"i1ex",
+            b"l1:a",
+            b"i99999999999999999999e",
+        ];
+        for src in cases.iter() {
+            let e = crate::parse_borrowed(src).unwrap_err();
+            assert_eq!(
+                format!("{:?}", e),
+                format!("{:?}", crate::parse(src).unwrap_err())
+            );
+        }
+        let e = crate::parse_borrowed(b"d1:ai1e1:ai2ee").unwrap_err();
+        assert!(matches!(e, ParseErr::DuplicateKey { ref key, offset: 7 } if key == b"a"));
+    }
+}
@@ -0,0 +1,731 @@
+use crate::map::{self, Map};
+use crate::ser::{DICT_KEY, HEX_KEY};
+use crate::{BData, ParseErr};
+use serde::de::value::SeqDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize};
+use std::convert::TryInto;
+use std::fmt;
+use std::slice;
+
+impl de::Error for ParseErr {
+    fn custom<T: fmt::Display>(msg: T) -> ParseErr {
+        ParseErr::ParseFailure(msg.to_string().into())
+    }
+}
+
+/// 解析 bencode 数据并转换为 `T`
+pub fn from_bytes<T: DeserializeOwned>(src: &[u8]) -> Result<T, ParseErr> {
+    let data = crate::parse(src)?;
+    from_bdata(&data)
+}
+
+/// 将已解析的数据转换为 `T`
+pub fn from_bdata<'de, T: Deserialize<'de>>(data: &'de BData) -> Result<T, ParseErr> {
+    T::deserialize(Deserializer::new(data))
+}
+
+/// 从任意自描述的格式中还原 `BData`, 与 `Serialize` 的输出对应.
+///
+/// 字符串与字节为 `BString`, 整数为 `Number` (超出 `i64` 时为 `BigNumber`), `bool` 为 `0` 或 `1`,
+/// 序列为 `List`, map 为 `Dict`. 文本格式中只有一个键 `$hex` 且值为十六进制字符串的 map
+/// 还原为 `BString`, 值为 `{"$dict": <值>}` 时还原为唯一的键为 `$hex` 的字典.
+impl<'de> Deserialize<'de> for BData {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<BData, D::Error> {
+        let human_readable = d.is_human_readable();
+        d.deserialize_any(BDataVisitor { human_readable })
+    }
+}
+
+/// `human_readable` 为 true 时还原 `Serialize` 在文本格式中写入的 `$hex`
+struct BDataVisitor {
+    human_readable: bool,
+}
+
+impl BDataVisitor {
+    fn integer<T: TryInto<i64> + ToString + Copy>(n: T) -> BData {
+        match n.try_into() {
+            Ok(n) => BData::Number(n),
+            Err(_) => BData::BigNumber(n.to_string().into_bytes()),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for BDataVisitor {
+    type Value = BData;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a bencode value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<BData, E> {
+        Ok(BData::Number(v as i64))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<BData, E> {
+        Ok(BData::Number(v))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<BData, E> {
+        Ok(BDataVisitor::integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<BData, E> {
+        Ok(BDataVisitor::integer(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<BData, E> {
+        Ok(BDataVisitor::integer(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<BData, E> {
+        Ok(BData::from(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<BData, E> {
+        Ok(BData::from(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<BData, E> {
+        Ok(BData::from(v))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<BData, E> {
+        Ok(BData::from(v))
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<BData, D::Error> {
+        d.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<BData, A::Error> {
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(v) = seq.next_element()? {
+            list.push(v);
+        }
+        Ok(BData::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<BData, A::Error> {
+        let mut dict = Map::new();
+        while let Some((KeyBuf(k), v)) = map.next_entry()? {
+            dict.insert(k, v);
+        }
+        if self.human_readable && dict.len() == 1 {
+            return Ok(unescape(dict));
+        }
+        Ok(BData::Dict(dict))
+    }
+}
+
+/// 还原只有一个键 `$hex` 的 map, 值不是 `Serialize` 写入的格式时保持原样
+fn unescape(mut dict: Map<Vec<u8>, BData>) -> BData {
+    match dict.get_mut(HEX_KEY.as_bytes()) {
+        Some(BData::BString(v)) => {
+            if let Some(v) = unhex(v) {
+                return BData::BString(v);
+            }
+        }
+        Some(v) => {
+            if let BData::Dict(inner) = v {
+                if inner.len() == 1 {
+                    if let Some(inner) = map::remove(inner, DICT_KEY.as_bytes()) {
+                        *v = inner;
+                    }
+                }
+            }
+        }
+        None => {}
+    }
+    BData::Dict(dict)
+}
+
+fn unhex(v: &[u8]) -> Option<Vec<u8>> {
+    let pairs = v.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    pairs
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect()
+}
+
+/// 字典的键, 可以从字符串或字节读取
+struct KeyBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for KeyBuf {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<KeyBuf, D::Error> {
+        d.deserialize_bytes(KeyVisitor)
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = KeyBuf;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string key")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<KeyBuf, E> {
+        Ok(KeyBuf(v.as_bytes().to_vec()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<KeyBuf, E> {
+        Ok(KeyBuf(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<KeyBuf, E> {
+        Ok(KeyBuf(v))
+    }
+}
+
+/// 以 `BData` 为输入的 `serde::Deserializer`.
+///
+/// 字符串可以转换为 `String` 或 `Vec<u8>`, 字典可以转换为结构体或 map, 列表可以转换为序列或元组.
+/// 整数转换为较窄的类型时会检查范围, `bool` 对应 `i0e` 与 `i1e`.
+/// 没有值的枚举成员对应字符串, 其他成员对应只有一个键值对的字典.
+pub struct Deserializer<'de> {
+    data: &'de BData,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(data: &'de BData) -> Deserializer<'de> {
+        Deserializer { data }
+    }
+
+    fn unexpected(&self) -> Unexpected<'de> {
+        match self.data {
+            BData::BString(s) => Unexpected::Bytes(s),
+            BData::Number(n) => Unexpected::Signed(*n),
+            BData::BigNumber(_) => Unexpected::Other("big integer"),
+            BData::List(_) => Unexpected::Seq,
+            BData::Dict(_) => Unexpected::Map,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = ParseErr;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        match self.data {
+            BData::BString(s) => visitor.visit_borrowed_bytes(s),
+            BData::Number(n) => visitor.visit_i64(*n),
+            BData::BigNumber(n) => {
+                // 超出 i64 范围的整数尝试以 u64 或 128 位整数读取
+                let literal = std::str::from_utf8(n).unwrap_or_default();
+                if let Ok(v) = literal.parse::<u64>() {
+                    visitor.visit_u64(v)
+                } else if let Ok(v) = literal.parse::<u128>() {
+                    visitor.visit_u128(v)
+                } else if let Ok(v) = literal.parse::<i128>() {
+                    visitor.visit_i128(v)
+                } else {
+                    Err(de::Error::invalid_type(self.unexpected(), &visitor))
+                }
+            }
+            BData::List(list) => visitor.visit_seq(ListAccess { iter: list.iter() }),
+            BData::Dict(dict) => visitor.visit_map(DictAccess {
+                iter: dict.iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        match self.data {
+            BData::Number(0) => visitor.visit_bool(false),
+            BData::Number(1) => visitor.visit_bool(true),
+            _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        match self.data {
+            BData::BString(s) => match std::str::from_utf8(s) {
+                Ok(s) => visitor.visit_borrowed_str(s),
+                Err(_) => visitor.visit_borrowed_bytes(s),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        self.deserialize_str(visitor)
+    }
+
+    /// 字符串作为 `u8` 序列读取, 使 `Vec<u8>` 与 `[u8; N]` 可以直接对应字符串
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        match self.data {
+            BData::BString(s) => {
+                let mut seq = SeqDeserializer::new(s.iter().copied());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ParseErr> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ParseErr> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// bencode 中没有空值, 出现的值都视为 `Some`, 缺少的字段由 serde 处理为 `None`
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParseErr> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParseErr> {
+        let (name, value) = match self.data {
+            BData::BString(s) => (s, None),
+            BData::Dict(dict) if dict.len() == 1 => match dict.iter().next() {
+                Some((name, value)) => (name, Some(value)),
+                None => return Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+            },
+            _ => return Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        };
+        visitor.visit_enum(Variant { name, value })
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64
+        bytes byte_buf unit unit_struct map struct
+    }
+}
+
+struct ListAccess<'de> {
+    iter: slice::Iter<'de, BData>,
+}
+
+impl<'de> SeqAccess<'de> for ListAccess<'de> {
+    type Error = ParseErr;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, ParseErr> {
+        match self.iter.next() {
+            Some(v) => seed.deserialize(Deserializer::new(v)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct DictAccess<'de> {
+    iter: map::Iter<'de, Vec<u8>, BData>,
+    /// 已读取键, 尚未读取的值
+    value: Option<&'de BData>,
+}
+
+impl<'de> MapAccess<'de> for DictAccess<'de> {
+    type Error = ParseErr;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ParseErr> {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v);
+                seed.deserialize(KeyDeserializer { key: k }).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ParseErr> {
+        match self.value.take() {
+            Some(v) => seed.deserialize(Deserializer::new(v)),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// 字典的键, 是合法的 UTF-8 时作为字符串读取, 否则作为字节读取
+struct KeyDeserializer<'de> {
+    key: &'de [u8],
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'de> {
+    type Error = ParseErr;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseErr> {
+        match std::str::from_utf8(self.key) {
+            Ok(s) => visitor.visit_borrowed_str(s),
+            Err(_) => visitor.visit_borrowed_bytes(self.key),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParseErr> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple tuple_struct map struct
+        enum identifier ignored_any
+    }
+}
+
+/// 枚举成员的名称以及其携带的值
+struct Variant<'de> {
+    name: &'de [u8],
+    value: Option<&'de BData>,
+}
+
+impl<'de> EnumAccess<'de> for Variant<'de> {
+    type Error = ParseErr;
+    type Variant = VariantValue<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantValue<'de>), ParseErr> {
+        let name = seed.deserialize(KeyDeserializer { key: self.name })?;
+        Ok((name, VariantValue { value: self.value }))
+    }
+}
+
+struct VariantValue<'de> {
+    value: Option<&'de BData>,
+}
+
+impl<'de> VariantValue<'de> {
+    fn value(self) -> Result<Deserializer<'de>, ParseErr> {
+        match self.value {
+            Some(v) => Ok(Deserializer::new(v)),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"variant with value",
+            )),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantValue<'de> {
+    type Error = ParseErr;
+
+    fn unit_variant(self) -> Result<(), ParseErr> {
+        match self.value {
+            None => Ok(()),
+            Some(_) => Err(de::Error::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, ParseErr> {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, ParseErr> {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParseErr> {
+        de::Deserializer::deserialize_any(self.value()?, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{BData, ParseErr, ParseErrKind};
+    use serde::{Deserialize, Serialize};
+    use serde_bytes::ByteBuf;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct File {
+        length: u64,
+        path: Vec<String>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Info {
+        name: String,
+        #[serde(rename = "piece length")]
+        piece_length: u32,
+        pieces: Vec<u8>,
+        private: Option<bool>,
+        files: Option<Vec<File>>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct TorrentMeta {
+        announce: String,
+        #[serde(rename = "announce-list")]
+        announce_list: Option<Vec<Vec<String>>>,
+        comment: Option<String>,
+        info: Info,
+    }
+
+    #[test]
+    fn from_bytes_test() {
+        let src = b"d8:announce8:http://t13:announce-listll8:http://tel8:http://uee\
+            4:infod5:filesld6:lengthi10e4:pathl1:a5:b.txteee4:name3:dir\
+            12:piece lengthi16384e6:pieces4:\x00\xff\x10\x207:privatei1eee";
+        let meta: TorrentMeta = crate::from_bytes(src).unwrap();
+        let expected = TorrentMeta {
+            announce: "http://t".to_string(),
+            announce_list: Some(vec![
+                vec!["http://t".to_string()],
+                vec!["http://u".to_string()],
+            ]),
+            comment: None,
+            info: Info {
+                name: "dir".to_string(),
+                piece_length: 16384,
+                pieces: vec![0x00, 0xff, 0x10, 0x20],
+                private: Some(true),
+                files: Some(vec![File {
+                    length: 10,
+                    path: vec!["a".to_string(), "b.txt".to_string()],
+                }]),
+            },
+        };
+        assert_eq!(meta, expected);
+    }
+
+    #[test]
+    fn from_bdata_test() {
+        let data = bdata!({"a" => [1], "b" => [1, 2], "c" => []});
+        let map: BTreeMap<String, Vec<i64>> = crate::from_bdata(&data).unwrap();
+        assert_eq!(map["b"], vec![1, 2]);
+        assert!(map["c"].is_empty());
+        let v: (i64, String) = crate::from_bdata(&bdata!([7, "s"])).unwrap();
+        assert_eq!(v, (7, "s".to_string()));
+        let v: [u8; 3] = crate::from_bdata(&bdata!("abc")).unwrap();
+        assert_eq!(&v, b"abc");
+
+        // 借用输入中的字符串
+        let data = bdata!({"k" => "v"});
+        let map: HashMap<&str, &str> = crate::from_bdata(&data).unwrap();
+        assert_eq!(map["k"], "v");
+    }
+
+    #[test]
+    fn enum_test() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        enum E {
+            Unit,
+            Newtype(i32),
+            Tuple(i32, String),
+            Struct { x: i8 },
+        }
+        let v: Vec<E> =
+            crate::from_bytes(b"l4:Unitd7:Newtypei1eed5:Tupleli2e1:aeed6:Structd1:xi-3eeee")
+                .unwrap();
+        assert_eq!(
+            v,
+            vec![
+                E::Unit,
+                E::Newtype(1),
+                E::Tuple(2, "a".to_string()),
+                E::Struct { x: -3 }
+            ]
+        );
+        assert!(crate::from_bytes::<E>(b"4:Tail").is_err());
+        assert!(crate::from_bytes::<E>(b"d7:Newtypei1e4:Unit0:e").is_err());
+    }
+
+    #[test]
+    fn from_bytes_err_test() {
+        // 数值超出范围
+        let v = crate::from_bytes::<u8>(b"i256e");
+        assert_eq!(v.unwrap_err().kind(), ParseErrKind::Conversion);
+        assert!(crate::from_bytes::<u32>(b"i-1e").is_err());
+        assert_eq!(crate::from_bytes::<i16>(b"i-32768e").unwrap(), -32768);
+
+        // 类型不匹配
+        let v = crate::from_bytes::<String>(b"i1e").unwrap_err();
+        assert_eq!(
+            v.to_string(),
+            "invalid value: invalid type: integer `1`, expected a string"
+        );
+        assert!(crate::from_bytes::<String>(b"1:\xff").is_err());
+        assert!(crate::from_bytes::<bool>(b"i2e").is_err());
+
+        // 缺少字段
+        let v = crate::from_bytes::<File>(b"d6:lengthi1ee").unwrap_err();
+        assert_eq!(v.to_string(), "invalid value: missing field `path`");
+
+        // 语法错误原样返回
+        let v = crate::from_bytes::<File>(b"d6:length");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { .. })));
+    }
+
+    #[test]
+    fn bdata_serde_test() {
+        let data = bdata!({
+            "announce" => "http://t",
+            "info" => {
+                "length" => -1024,
+                "pieces" => &b"\x00\xff\x10"[..],
+                "files" => [["a", 1], [], {}],
+            },
+            "0" => 0,
+        });
+        let json = serde_json::to_string(&data).unwrap();
+        assert!(json.contains(r#""pieces":{"$hex":"00ff10"}"#));
+        assert_eq!(serde_json::from_str::<BData>(&json).unwrap(), data);
+
+        let mut with_binary_key = data.clone();
+        if let BData::Dict(dict) = &mut with_binary_key {
+            dict.insert(b"\xfe".to_vec(), bdata!(&b"\xfe"[..]));
+        }
+        assert!(serde_json::to_string(&with_binary_key).is_err());
+        let cbor = serde_cbor::to_vec(&with_binary_key).unwrap();
+        let v: BData = serde_cbor::from_slice(&cbor).unwrap();
+        assert_eq!(v, with_binary_key);
+
+        // bencode 本身
+        let v: BData = crate::from_bdata(&with_binary_key).unwrap();
+        assert_eq!(v, with_binary_key);
+        assert_eq!(crate::to_bdata(&with_binary_key).unwrap(), with_binary_key);
+    }
+
+    #[test]
+    fn bdata_hex_key_test() {
+        let cases = [
+            bdata!({"$hex" => "00ff"}),
+            bdata!({"$hex" => &b"\xff"[..]}),
+            bdata!({"$hex" => {"$dict" => 1}}),
+            bdata!({"$hex" => {}}),
+            bdata!([{"$hex" => "ab"}, {"$hex" => "ab", "x" => 1}]),
+        ];
+        for data in cases.iter() {
+            // 非文本格式中 `$hex` 没有特殊含义
+            assert_eq!(crate::from_bdata::<BData>(data).unwrap(), *data);
+            let cbor = serde_cbor::to_vec(data).unwrap();
+            assert_eq!(serde_cbor::from_slice::<BData>(&cbor).unwrap(), *data);
+
+            let json = serde_json::to_string(data).unwrap();
+            assert_eq!(serde_json::from_str::<BData>(&json).unwrap(), *data);
+        }
+        let json = serde_json::to_string(&cases[0]).unwrap();
+        assert_eq!(json, r#"{"$hex":{"$dict":"00ff"}}"#);
+        let v: BData = serde_json::from_str(r#"{"$hex":"00ff"}"#).unwrap();
+        assert_eq!(v, bdata!(&b"\x00\xff"[..]));
+    }
+
+    #[test]
+    fn bdata_from_json_test() {
+        let v: BData =
+            serde_json::from_str(r#"[true, 18446744073709551615, {"$hex": "zz"}]"#).unwrap();
+        let expected = bdata!([
+            1,
+            BData::BigNumber(b"18446744073709551615".to_vec()),
+            {"$hex" => "zz"},
+        ]);
+        assert_eq!(v, expected);
+        assert!(serde_json::from_str::<BData>("1.5").is_err());
+        assert!(serde_json::from_str::<BData>("null").is_err());
+    }
+
+    #[test]
+    fn serde_bytes_test() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Info {
+            #[serde(with = "serde_bytes")]
+            pieces: Vec<u8>,
+            length: i64,
+        }
+        let info = Info {
+            pieces: vec![0, 0xff, 0x10],
+            length: 3,
+        };
+        let encoded = crate::to_bytes(&info).unwrap();
+        assert_eq!(encoded, &b"d6:lengthi3e6:pieces3:\x00\xff\x10e"[..]);
+        assert_eq!(crate::from_bytes::<Info>(&encoded).unwrap(), info);
+
+        let v: ByteBuf = crate::from_bytes(b"2:\xfe\xff").unwrap();
+        assert_eq!(v.as_slice(), b"\xfe\xff");
+        assert_eq!(
+            crate::to_bdata(&v).unwrap(),
+            BData::BString(b"\xfe\xff".to_vec())
+        );
+    }
+
+    #[test]
+    fn big_number_test() {
+        let opts = crate::ParseOptions {
+            big_numbers: true,
+            ..Default::default()
+        };
+        let data = crate::parse_with_options(
+            b"li18446744073709551615ei-170141183460469231731687303715884105728ee",
+            &opts,
+        )
+        .unwrap();
+        let v: (u64, i128) = crate::from_bdata(&data).unwrap();
+        assert_eq!(v, (u64::MAX, i128::MIN));
+    }
+}
@@ -0,0 +1,377 @@
+use crate::BData;
+use serde_json::{Map, Number, Value};
+use std::error::Error;
+use std::fmt;
+
+/// 不是合法 UTF-8 的字符串在 JSON 中的表示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytesEncoding {
+    /// 替换非法字节为 `U+FFFD`, 无法还原
+    Lossy,
+    /// 带填充的标准 base64 字符串
+    Base64,
+    /// 小写的十六进制字符串
+    Hex,
+    /// 由各个字节的值组成的数组
+    Array,
+}
+
+/// 转换为 JSON 时的选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// 默认为 `BytesEncoding::Hex`
+    pub bytes: BytesEncoding,
+}
+
+impl Default for JsonOptions {
+    fn default() -> JsonOptions {
+        JsonOptions {
+            bytes: BytesEncoding::Hex,
+        }
+    }
+}
+
+/// 以默认选项转换为 JSON, 参见 `to_json_with`.
+///
+/// 不是 UTF-8 的字符串转换为十六进制字符串:
+///
+/// ```
+/// use serde_json::json;
+/// let data = fraux_rs::parse(b"d4:name5:a.txt6:pieces2:\x00\xff6:lengthi-1ee").unwrap();
+/// let expected = json!({"length": -1, "name": "a.txt", "pieces": "00ff"});
+/// assert_eq!(fraux_rs::to_json(&data), expected);
+/// ```
+pub fn to_json(data: &BData) -> Value {
+    to_json_with(data, &JsonOptions::default())
+}
+
+/// 转换为 JSON.
+///
+/// 合法 UTF-8 的字符串转换为 JSON 字符串, 其他字符串按 `opts.bytes` 转换.
+/// 超出 `u64` 与 `i64` 范围的 `BigNumber` 转换为数字组成的字符串.
+///
+/// 字典的键只能是字符串, 为了不同的键不会相互覆盖, 不是 UTF-8 的键转换为 `$` 加上
+/// 编码后的字符串 (`Base64` 时为 base64, 其他情况为十六进制), 以 `$` 开头的键再加上一个 `$`:
+///
+/// ```
+/// use serde_json::json;
+/// let data = fraux_rs::bdata!({&b"\xff"[..] => 1, "ff" => 2, "$ff" => 3});
+/// let expected = json!({"$ff": 1, "ff": 2, "$$ff": 3});
+/// assert_eq!(fraux_rs::to_json(&data), expected);
+/// ```
+pub fn to_json_with(data: &BData, opts: &JsonOptions) -> Value {
+    match data {
+        BData::BString(s) => match std::str::from_utf8(s) {
+            Ok(s) => Value::String(s.to_string()),
+            Err(_) if opts.bytes == BytesEncoding::Array => {
+                Value::Array(s.iter().map(|b| Value::from(*b)).collect())
+            }
+            Err(_) => Value::String(encode_bytes(s, opts.bytes)),
+        },
+        BData::Number(n) => Value::from(*n),
+        BData::BigNumber(n) => {
+            let literal = String::from_utf8_lossy(n);
+            match literal.parse::<u64>() {
+                Ok(n) => Value::Number(Number::from(n)),
+                Err(_) => Value::String(literal.into_owned()),
+            }
+        }
+        BData::List(list) => Value::Array(list.iter().map(|v| to_json_with(v, opts)).collect()),
+        BData::Dict(dict) => {
+            let mut map = Map::new();
+            for (k, v) in dict {
+                map.insert(json_key(k, opts.bytes), to_json_with(v, opts));
+            }
+            Value::Object(map)
+        }
+    }
+}
+
+/// JSON 转换为 `BData` 时遇到无法表示的值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    /// 出错的值在 JSON 中的路径, 如 `$.info.files[0]`
+    pub path: String,
+    /// 值的类型: `float`, `boolean` 或 `null`
+    pub found: &'static str,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at {} cannot be represented in bencode",
+            self.found, self.path
+        )
+    }
+}
+
+impl Error for ConvertError {}
+
+/// 从 JSON 构建数据.
+///
+/// 对象转换为字典, 数组转换为列表, 字符串转换为其 UTF-8 编码, 整数转换为 `Number`,
+/// 超出 `i64` 范围的整数转换为 `BigNumber`. 浮点数, `bool` 与 `null` 返回错误.
+pub fn from_json(v: &Value) -> Result<BData, ConvertError> {
+    let mut path = String::from("$");
+    convert(v, &mut path)
+}
+
+fn convert(v: &Value, path: &mut String) -> Result<BData, ConvertError> {
+    let found = match v {
+        Value::String(s) => return Ok(BData::from(s.as_str())),
+        Value::Number(n) => {
+            if let Some(n) = n.as_i64() {
+                return Ok(BData::Number(n));
+            }
+            if let Some(n) = n.as_u64() {
+                return Ok(BData::BigNumber(n.to_string().into_bytes()));
+            }
+            "float"
+        }
+        Value::Array(list) => {
+            let mut out = Vec::with_capacity(list.len());
+            for (i, v) in list.iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{}]", i));
+                out.push(convert(v, path)?);
+                path.truncate(len);
+            }
+            return Ok(BData::List(out));
+        }
+        Value::Object(map) => {
+            let mut out = crate::Map::new();
+            for (k, v) in map {
+                let len = path.len();
+                if !k.is_empty() && k.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    path.push('.');
+                    path.push_str(k);
+                } else {
+                    path.push_str(&format!("[{:?}]", k));
+                }
+                out.insert(k.as_bytes().to_vec(), convert(v, path)?);
+                path.truncate(len);
+            }
+            return Ok(BData::Dict(out));
+        }
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    };
+    Err(ConvertError {
+        path: path.clone(),
+        found,
+    })
+}
+
+impl From<&BData> for Value {
+    fn from(data: &BData) -> Value {
+        to_json(data)
+    }
+}
+
+/// 字典的键转换为 JSON 的键, 不同的键一定得到不同的结果.
+///
+/// 编码后的字节不会以 `$` 开头, 因此不会与加倍了 `$` 的键相同
+fn json_key(k: &[u8], encoding: BytesEncoding) -> String {
+    match std::str::from_utf8(k) {
+        Ok(k) if k.starts_with(KEY_ESCAPE) => format!("{}{}", KEY_ESCAPE, k),
+        Ok(k) => k.to_string(),
+        // 有损转换可能使不同的键相同, 此时同样使用十六进制
+        Err(_) if encoding == BytesEncoding::Lossy => {
+            format!("{}{}", KEY_ESCAPE, encode_bytes(k, BytesEncoding::Hex))
+        }
+        Err(_) => format!("{}{}", KEY_ESCAPE, encode_bytes(k, encoding)),
+    }
+}
+
+/// 转义字典的键时使用的前缀
+const KEY_ESCAPE: char = '$';
+
+/// 将字节编码为字符串, `Array` 只能用于值, 用于字符串时按 `Hex` 处理
+fn encode_bytes(s: &[u8], encoding: BytesEncoding) -> String {
+    match encoding {
+        BytesEncoding::Lossy => String::from_utf8_lossy(s).into_owned(),
+        BytesEncoding::Base64 => base64(s),
+        BytesEncoding::Hex | BytesEncoding::Array => {
+            s.iter().map(|b| format!("{:02x}", b)).collect()
+        }
+    }
+}
+
+fn base64(s: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(s.len().div_ceil(3) * 4);
+    for chunk in s.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BytesEncoding, JsonOptions};
+    use crate::BData;
+    use serde_json::{json, Value};
+
+    fn torrent() -> BData {
+        bdata!({
+            "announce" => "http://t",
+            "info" => {
+                "length" => -1024,
+                "pieces" => &b"\x00\xff\x10\xfe"[..],
+                "files" => [["a", 1], []],
+            },
+        })
+    }
+
+    #[test]
+    fn to_json_test() {
+        let v = crate::to_json(&torrent());
+        let expected = json!({
+            "announce": "http://t",
+            "info": {
+                "files": [["a", 1], []],
+                "length": -1024,
+                "pieces": "00ff10fe",
+            },
+        });
+        assert_eq!(v, expected);
+        assert_eq!(Value::from(&torrent()), expected);
+    }
+
+    #[test]
+    fn bytes_encoding_test() {
+        let cases = [
+            (BytesEncoding::Lossy, json!("\u{0}\u{fffd}\u{10}\u{fffd}")),
+            (BytesEncoding::Base64, json!("AP8Q/g==")),
+            (BytesEncoding::Hex, json!("00ff10fe")),
+            (BytesEncoding::Array, json!([0, 255, 16, 254])),
+        ];
+        for (bytes, pieces) in cases.iter() {
+            let opts = JsonOptions { bytes: *bytes };
+            let v = crate::to_json_with(&torrent(), &opts);
+            assert_eq!(v["info"]["pieces"], *pieces);
+            assert_eq!(v["announce"], json!("http://t"));
+        }
+
+        let cases: [(&[u8], &str); 4] =
+            [(b"", ""), (b"f", "Zg=="), (b"fo", "Zm8="), (b"foo", "Zm9v")];
+        for (src, encoded) in cases.iter() {
+            assert_eq!(super::base64(src), *encoded);
+        }
+    }
+
+    #[test]
+    fn from_json_test() {
+        let values = [
+            torrent(),
+            bdata!({"a b" => [[], {}], "" => 0, "n" => BData::BigNumber(b"18446744073709551615".to_vec())}),
+            bdata!([-1, "", "\u{4e2d}"]),
+        ];
+        for v in values.iter().skip(1) {
+            assert_eq!(crate::from_json(&crate::to_json(v)).unwrap(), *v);
+        }
+        // 不是 UTF-8 的字符串无法还原
+        let v = crate::from_json(&crate::to_json(&values[0])).unwrap();
+        let info = &v.as_dict().unwrap()[&b"info"[..]];
+        assert_eq!(info.as_dict().unwrap()[&b"pieces"[..]], bdata!("00ff10fe"));
+    }
+
+    #[test]
+    fn from_json_encode_test() {
+        let v: Value = serde_json::from_str(
+            r#"{"info": {"piece length": 16384, "name": "a", "length": 5}, "announce": "http://t"}"#,
+        )
+        .unwrap();
+        let data = crate::from_json(&v).unwrap();
+        // 键按字节顺序输出
+        assert_eq!(
+            crate::stringify(&data).unwrap(),
+            &b"d8:announce8:http://t4:infod6:lengthi5e4:name1:a12:piece lengthi16384eee"[..]
+        );
+    }
+
+    #[test]
+    fn from_json_err_test() {
+        let cases = [
+            (json!(1.5), "$", "float"),
+            (json!(null), "$", "null"),
+            (
+                json!({"info": {"files": [1, true]}}),
+                "$.info.files[1]",
+                "boolean",
+            ),
+            (json!({"piece length": null}), "$[\"piece length\"]", "null"),
+        ];
+        for (v, path, found) in cases.iter() {
+            let e = crate::from_json(v).unwrap_err();
+            assert_eq!(e.path, *path);
+            assert_eq!(e.found, *found);
+        }
+        let e = crate::from_json(&json!([0.5])).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "float at $[0] cannot be represented in bencode"
+        );
+    }
+
+    #[test]
+    fn to_json_key_and_number_test() {
+        let data = bdata!({
+            &b"\xff"[..] => BData::BigNumber(b"18446744073709551615".to_vec()),
+            "neg" => BData::BigNumber(b"-18446744073709551616".to_vec()),
+        });
+        let opts = JsonOptions {
+            bytes: BytesEncoding::Array,
+        };
+        let v = crate::to_json_with(&data, &opts);
+        assert_eq!(
+            v,
+            json!({"$ff": 18446744073709551615u64, "neg": "-18446744073709551616"})
+        );
+    }
+
+    #[test]
+    fn to_json_key_collision_test() {
+        // 编码后的键与已有的键相同时不能覆盖
+        let data = bdata!({
+            &b"\xff"[..] => 1,
+            &b"\xfe"[..] => 2,
+            "ff" => 3,
+            "$ff" => 4,
+            "$" => 5,
+            "/w==" => 6,
+            &b"$\xff"[..] => 7,
+        });
+        let cases = [
+            (
+                BytesEncoding::Hex,
+                json!({"$ff": 1, "$fe": 2, "ff": 3, "$$ff": 4, "$$": 5, "/w==": 6, "$24ff": 7}),
+            ),
+            (
+                BytesEncoding::Base64,
+                json!({"$/w==": 1, "$/g==": 2, "ff": 3, "$$ff": 4, "$$": 5, "/w==": 6, "$JP8=": 7}),
+            ),
+        ];
+        for (bytes, expected) in cases.iter() {
+            let v = crate::to_json_with(&data, &JsonOptions { bytes: *bytes });
+            assert_eq!(v, *expected);
+        }
+        for bytes in [BytesEncoding::Lossy, BytesEncoding::Array].iter() {
+            let v = crate::to_json_with(&data, &JsonOptions { bytes: *bytes });
+            assert_eq!(v, cases[0].1);
+        }
+    }
+}
@@ -1,418 +1,4133 @@
-use core::slice::Iter;
+//! bencode 的解析与编码.
+//!
+//! 默认开启的 `std` 功能提供读写文件与 `io::Read`/`io::Write` 相关的接口.
+//! 关闭后以 `#![no_std]` 编译, 只依赖 `alloc`, 此时错误类型实现 `core::error::Error`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{FromUtf8Error, String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::{Infallible, TryFrom};
+#[cfg(not(feature = "std"))]
+use core::error::Error;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::{Index, IndexMut};
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::{collections::BTreeMap, iter::Peekable};
+#[cfg(feature = "std")]
+use std::ffi::OsString;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "async")]
+mod async_io;
+mod borrowed;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "json")]
+mod json;
+pub mod map;
+mod pretty;
+mod push;
+#[cfg(feature = "serde")]
+mod ser;
+mod span;
+mod stats;
+mod token;
+#[cfg(feature = "torrent")]
+mod torrent;
+mod visit;
+mod walk;
+
+#[cfg(feature = "async")]
+pub use async_io::parse_async;
+pub use borrowed::{parse_borrowed, BDataRef};
+#[cfg(feature = "serde")]
+pub use de::{from_bdata, from_bytes, Deserializer};
+#[cfg(feature = "json")]
+pub use json::{from_json, to_json, to_json_with, BytesEncoding, ConvertError, JsonOptions};
+pub use map::Map;
+pub use pretty::PrettyOptions;
+pub use push::Parser;
+#[cfg(feature = "serde")]
+pub use ser::{to_bdata, to_bytes};
+pub use span::{SpanChildren, SpanTree};
+pub use stats::Stats;
+pub use token::{Token, Tokenizer, DEFAULT_MAX_DEPTH};
+#[cfg(feature = "torrent")]
+pub use torrent::{info_bytes, info_hash, info_hash_v2};
+pub use visit::{visit, Visitor};
+
+/// 供宏使用, 不属于公开的接口
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+}
+
+use map::Entry;
+use span::SpanBuilder;
+use token::Lexer;
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Clone)]
 pub enum BData {
     BString(Vec<u8>),
-    Number(i32),
+    Number(i64),
+    /// 超出 `i64` 范围的整数, 保存原始的数字 (包括符号), 编码时原样输出.
+    /// 仅在 `ParseOptions::big_numbers` 开启时产生
+    BigNumber(Vec<u8>),
     List(Vec<BData>),
-    Dict(BTreeMap<String, BData>),
-}
-#[derive(Debug)]
-pub enum ParseErr {
-    /// 数据格式错误
-    SyntaxError,
-    /// 数据缺失
-    DataException,
-    /// 转换中出现的异常
-    ParseFailure(Box<dyn Error>),
+    /// 字典的键按原始字节保存, 不要求是合法的 UTF-8
+    Dict(Map<Vec<u8>, BData>),
 }
 
-pub fn parse(src: &Vec<u8>) -> Result<BData, ParseErr> {
-    let mut peekable: Peekable<Iter<'_, u8>> = src.iter().peekable();
-    let v = parse_data(&mut peekable);
-    v
+/// 数据的类型, 由 `BData::kind` 返回
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BKind {
+    Bytes,
+    /// 包括 `BData::Number` 与 `BData::BigNumber`
+    Number,
+    List,
+    Dict,
 }
 
-fn parse_data(mut s: &mut Peekable<Iter<u8>>) -> Result<BData, ParseErr> {
-    let res = match s.peek() {
-        Some(b'0'..=b'9') => parse_string(&mut s),
-        Some(b'i') => parse_number(&mut s),
-        Some(b'l') => parse_list(&mut s),
-        Some(b'd') => parse_dict(&mut s),
-        Some(_) => return Err(ParseErr::SyntaxError),
-        None => return Err(ParseErr::DataException),
-    };
+impl BData {
+    /// 字符串的原始字节, 其他类型返回 `None`
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            BData::BString(s) => Some(s),
+            _ => None,
+        }
+    }
 
-    res
-}
+    /// 字符串是合法的 UTF-8 时返回 `&str`, 否则返回 `None`
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_bytes().and_then(|s| core::str::from_utf8(s).ok())
+    }
 
-fn parse_number(s: &mut Peekable<Iter<u8>>) -> Result<BData, ParseErr> {
-    let cv = s.next();
-    match cv {
-        Some(b'i') => {
-            let mut symb = false;
-            let mut num = Vec::new();
-            loop {
-                let v = s.next();
-                match v {
-                    Some(b'0'..=b'9') => {
-                        num.push(v.unwrap().clone());
-                    }
-                    Some(b'+') | Some(b'-') => {
-                        if symb {
-                            return Err(ParseErr::SyntaxError);
-                        } else {
-                            num.push(v.unwrap().clone());
-                            symb = true;
-                        }
-                    }
-                    Some(b'e') => break,
-                    Some(_) => return Err(ParseErr::SyntaxError),
-                    None => return Err(ParseErr::DataException),
-                }
-            }
-            let v = String::from_utf8(num).and_then(|s| Ok(s.parse::<i32>()));
+    pub fn as_number(&self) -> Option<i64> {
+        match self {
+            BData::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
 
-            if let Ok(v) = v {
-                match v {
-                    Ok(n) => {
-                        return Ok(BData::Number(n));
-                    }
-                    Err(e) => {
-                        return Err(ParseErr::ParseFailure(Box::new(e)));
-                    }
-                }
-            } else {
-                return Err(ParseErr::ParseFailure(Box::new(v.unwrap_err())));
-            }
+    pub fn as_list(&self) -> Option<&[BData]> {
+        match self {
+            BData::List(list) => Some(list),
+            _ => None,
         }
-        Some(_) => return Err(ParseErr::SyntaxError),
-        None => return Err(ParseErr::DataException),
     }
-}
 
-fn parse_string(s: &mut Peekable<Iter<u8>>) -> Result<BData, ParseErr> {
-    let mut len: usize = 0;
-    loop {
-        let v = s.next();
-        match v {
-            Some(b'0'..=b'9') => {
-                len = len * 10 + (v.unwrap() - b'0') as usize;
-            }
-            Some(b':') => {
-                break;
-            }
-            Some(_) => return Err(ParseErr::SyntaxError),
-            None => return Err(ParseErr::DataException),
+    pub fn as_dict(&self) -> Option<&Map<Vec<u8>, BData>> {
+        match self {
+            BData::Dict(dict) => Some(dict),
+            _ => None,
         }
     }
 
-    let mut i = 0;
-    let mut bstr = Vec::new();
+    /// 字典中键为 `key` 的值, 不是字典或键不存在时返回 `None`.
+    ///
+    /// 键可以是 `&str` 或 `&[u8]` 等任何可以转换为字节的类型
+    pub fn get<K: AsRef<[u8]> + ?Sized>(&self, key: &K) -> Option<&BData> {
+        self.as_dict()?.get(key.as_ref())
+    }
 
-    while i < len {
-        match s.next() {
-            Some(c) => {
-                bstr.push(c.clone());
-                i += 1;
-            }
-            None => return Err(ParseErr::DataException),
+    pub fn get_mut<K: AsRef<[u8]> + ?Sized>(&mut self, key: &K) -> Option<&mut BData> {
+        self.as_dict_mut()?.get_mut(key.as_ref())
+    }
+
+    /// 将字典 `other` 递归地合并到字典中, 冲突的处理方式由 `strategy` 决定.
+    ///
+    /// 同一个键的值都是字典时继续合并, 类型不同 (如字典与列表) 时作为冲突处理.
+    /// `self` 不是字典时不做任何事
+    ///
+    /// ```
+    /// use fraux_rs::{bdata, ConflictPolicy, ListPolicy, MergeStrategy};
+    /// let mut settings = bdata!({"trackers" => ["a"], "port" => 1});
+    /// let strategy = MergeStrategy {
+    ///     conflicts: ConflictPolicy::KeepSelf,
+    ///     lists: ListPolicy::Concat,
+    /// };
+    /// settings.merge(bdata!({"trackers" => ["b"], "port" => 2}), strategy);
+    /// assert_eq!(settings, bdata!({"trackers" => ["a", "b"], "port" => 1}));
+    /// ```
+    pub fn merge(&mut self, other: BData, strategy: MergeStrategy) {
+        if self.is_dict() {
+            merge_with(self, other, strategy);
         }
     }
 
-    Ok(BData::BString(bstr))
-}
+    /// 字典中键 `key` 对应的 `BEntry`, 用于插入或修改值.
+    ///
+    /// `or_insert_dict` 返回的值可以继续调用 `entry`, 一次创建多层字典:
+    ///
+    /// ```
+    /// use fraux_rs::bdata;
+    /// let mut data = bdata!({});
+    /// *data.entry("stats").or_insert_dict().entry("peer1").or_insert(0) = 5.into();
+    /// assert_eq!(data, bdata!({"stats" => {"peer1" => 5}}));
+    /// ```
+    ///
+    /// 与 `IndexMut<&str>` 相同, 不是字典时 panic. 不希望 panic 时使用 `as_dict_mut` 与 `Map::entry`
+    pub fn entry<K: Into<Vec<u8>>>(&mut self, key: K) -> BEntry<'_> {
+        let key = key.into();
+        match self {
+            BData::Dict(dict) => BEntry(dict.entry(key)),
+            v => panic!(
+                "cannot index {} with key {:?}",
+                v.type_name(),
+                String::from_utf8_lossy(&key)
+            ),
+        }
+    }
 
-fn parse_list(s: &mut Peekable<Iter<u8>>) -> Result<BData, ParseErr> {
-    let c = s.next();
-    match c {
-        Some(b'l') => {
-            let mut list = std::vec::Vec::new();
-            loop {
-                let p = s.peek();
-                match p {
-                    Some(b'e') => {
-                        s.next();
-                        return Ok(BData::List(list));
-                    }
-                    Some(_) => {
-                        let v = parse_data(s);
-                        match v {
-                            Ok(data) => {
-                                list.push(data);
-                            }
-                            Err(_) => {
-                                return v;
-                            }
-                        };
-                    }
-                    None => {
-                        return Err(ParseErr::DataException);
-                    }
-                }
-            }
+    /// 是字典且包含键 `key`
+    pub fn contains_key<K: AsRef<[u8]> + ?Sized>(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 在字典中插入键值对, 返回键原来对应的值.
+    ///
+    /// 与 `get` 等方法一致, 不是字典时不做任何事并返回 `None`, 可以先用 `is_dict` 判断
+    pub fn insert<K: Into<Vec<u8>>, V: Into<BData>>(&mut self, key: K, value: V) -> Option<BData> {
+        self.as_dict_mut()?.insert(key.into(), value.into())
+    }
+
+    /// 从字典中删除键 `key`, 返回对应的值. 不是字典或键不存在时返回 `None`
+    pub fn remove<K: AsRef<[u8]> + ?Sized>(&mut self, key: &K) -> Option<BData> {
+        map::remove(self.as_dict_mut()?, key.as_ref())
+    }
+
+    /// 列表中下标为 `index` 的元素, 不是列表或越界时返回 `None`
+    pub fn get_index(&self, index: usize) -> Option<&BData> {
+        self.as_list()?.get(index)
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut BData> {
+        self.as_list_mut()?.get_mut(index)
+    }
+
+    /// 在列表末尾添加元素, 返回新元素的可变引用. 不是列表时不做任何事并返回 `None`
+    pub fn push<V: Into<BData>>(&mut self, value: V) -> Option<&mut BData> {
+        let list = self.as_list_mut()?;
+        list.push(value.into());
+        list.last_mut()
+    }
+
+    /// 删除并返回列表的最后一个元素, 不是列表或列表为空时返回 `None`
+    pub fn pop(&mut self) -> Option<BData> {
+        self.as_list_mut()?.pop()
+    }
+
+    pub fn as_bytes_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match self {
+            BData::BString(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number_mut(&mut self) -> Option<&mut i64> {
+        match self {
+            BData::Number(n) => Some(n),
+            _ => None,
         }
-        Some(_) => return Err(ParseErr::SyntaxError),
-        None => return Err(ParseErr::DataException),
     }
-}
 
-fn parse_dict(s: &mut std::iter::Peekable<std::slice::Iter<'_, u8>>) -> Result<BData, ParseErr> {
-    let p = s.next();
-    match p {
-        Some(b'd') => {
-            let mut map = BTreeMap::new();
-            loop {
-                let p = s.peek();
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<BData>> {
+        match self {
+            BData::List(list) => Some(list),
+            _ => None,
+        }
+    }
 
-                match p {
-                    Some(b'e') => {
-                        s.next();
-                        return Ok(BData::Dict(map));
-                    }
-                    Some(_) => {
-                        let data = parse_string(s);
-                        let key;
-                        match data {
-                            Ok(BData::BString(k)) => key = k,
-                            Ok(_) => return Err(ParseErr::SyntaxError),
-                            Err(_) => return data,
-                        }
-
-                        if let Ok(k) = String::from_utf8(key) {
-                            let v = parse_data(s);
-                            match v {
-                                Ok(data) => {
-                                    map.insert(k, data);
-                                }
-                                Err(_) => return v,
-                            }
-                        }
-                    }
-                    None => return Err(ParseErr::DataException),
+    pub fn as_dict_mut(&mut self) -> Option<&mut Map<Vec<u8>, BData>> {
+        match self {
+            BData::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// 列表的元素, 其他类型返回 `None`
+    pub fn iter_list(&self) -> Option<core::slice::Iter<'_, BData>> {
+        self.as_list().map(|list| list.iter())
+    }
+
+    /// 按键的顺序遍历字典, 其他类型返回 `None`
+    pub fn iter_dict(&self) -> Option<impl Iterator<Item = (&[u8], &BData)>> {
+        self.as_dict()
+            .map(|dict| dict.iter().map(|(k, v)| (k.as_slice(), v)))
+    }
+
+    /// 按路径查找子数据, 如 `info.files.0.length` 或 `info/files/0/length`.
+    ///
+    /// 路径以 `.` 或 `/` 分隔, 每段在字典中作为键, 在列表中作为下标.
+    /// 键本身包含分隔符时用 `\` 转义, 如 `a\.b`, `\\` 表示 `\` 本身.
+    /// 空路径表示数据本身, 键或下标不存在, 以及对字符串与整数继续查找时返回 `None`.
+    pub fn get_path(&self, path: &str) -> Option<&BData> {
+        split_path(path).iter().try_fold(self, |v, seg| match v {
+            BData::Dict(dict) => dict.get(seg.as_slice()),
+            BData::List(list) => list.get(parse_index(seg)?),
+            _ => None,
+        })
+    }
+
+    /// 与 `get_path` 相同, 返回可变引用
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut BData> {
+        self.segments_mut(&split_path(path))
+    }
+
+    fn segments_mut(&mut self, segs: &[Vec<u8>]) -> Option<&mut BData> {
+        segs.iter().try_fold(self, |v, seg| match v {
+            BData::Dict(dict) => dict.get_mut(seg.as_slice()),
+            BData::List(list) => list.get_mut(parse_index(seg)?),
+            _ => None,
+        })
+    }
+
+    /// 取出数据, 原处留下默认值 (空字符串), 不会复制数据
+    pub fn take(&mut self) -> BData {
+        core::mem::take(self)
+    }
+
+    /// 以 `new` 替换数据, 返回原来的数据
+    pub fn replace(&mut self, new: BData) -> BData {
+        core::mem::replace(self, new)
+    }
+
+    /// 按 `get_path` 的路径取出子数据, 并从所在的字典或列表中删除.
+    ///
+    /// 列表中之后的元素依次前移, 不会留下空位. 路径不存在时返回 `None` 且不做任何修改,
+    /// 空路径取出数据本身, 与 `take` 相同
+    ///
+    /// ```
+    /// use fraux_rs::bdata;
+    /// let mut data = bdata!({"info" => {"files" => ["a", "b", "c"]}});
+    /// assert_eq!(data.take_path("info/files/0"), Some(bdata!("a")));
+    /// assert_eq!(data, bdata!({"info" => {"files" => ["b", "c"]}}));
+    /// ```
+    pub fn take_path(&mut self, path: &str) -> Option<BData> {
+        let segs = split_path(path);
+        let (last, parent) = match segs.split_last() {
+            Some(split) => split,
+            None => return Some(self.take()),
+        };
+        match self.segments_mut(parent)? {
+            BData::Dict(dict) => map::remove(dict, last),
+            BData::List(list) => {
+                let index = parse_index(last)?;
+                if index < list.len() {
+                    Some(list.remove(index))
+                } else {
+                    None
                 }
             }
+            _ => None,
         }
-        Some(_) => return Err(ParseErr::SyntaxError),
-        None => return Err(ParseErr::DataException),
     }
-}
 
-pub fn stringify(data: &BData) -> Result<Vec<u8>, &str> {
-    let res = match data {
-        BData::BString(s) => stringify_string(s),
-        BData::Number(num) => stringify_number(num),
-        BData::List(vec) => stringify_list(vec),
-        BData::Dict(dict) => stringify_dict(dict),
-    };
-    res
-}
+    /// 按各段依次查找子数据, 与 `get_path` 相同但不需要解析路径字符串.
+    ///
+    /// 任何一段不存在或类型不符 (如对列表使用 `Key`) 时返回 `None`
+    ///
+    /// ```
+    /// use fraux_rs::{bdata, BPathSeg};
+    /// let data = bdata!({"info" => {"files" => [{"length" => 5}]}});
+    /// let path = [
+    ///     BPathSeg::Key(b"info"),
+    ///     BPathSeg::Key(b"files"),
+    ///     BPathSeg::Index(0),
+    ///     BPathSeg::Key(b"length"),
+    /// ];
+    /// assert_eq!(data.pointer(&path), Some(&bdata!(5)));
+    /// assert_eq!(data.pointer(&["info".into(), 0.into()]), None);
+    /// ```
+    pub fn pointer(&self, path: &[BPathSeg]) -> Option<&BData> {
+        path.iter().try_fold(self, |v, seg| match (v, seg) {
+            (BData::Dict(dict), BPathSeg::Key(k)) => dict.get(*k),
+            (BData::List(list), BPathSeg::Index(i)) => list.get(*i),
+            _ => None,
+        })
+    }
 
-fn stringify_number(data: &i32) -> Result<Vec<u8>, &'static str> {
-    let mut content = Vec::new();
-    content.push(b'i');
-    content.append(&mut format!("{}", data).as_bytes().to_vec());
-    content.push(b'e');
-    Ok(content)
-}
+    /// 与 `pointer` 相同, 返回可变引用
+    pub fn pointer_mut(&mut self, path: &[BPathSeg]) -> Option<&mut BData> {
+        path.iter().try_fold(self, |v, seg| match (v, seg) {
+            (BData::Dict(dict), BPathSeg::Key(k)) => dict.get_mut(*k),
+            (BData::List(list), BPathSeg::Index(i)) => list.get_mut(*i),
+            _ => None,
+        })
+    }
 
-fn stringify_string(data: &Vec<u8>) -> Result<Vec<u8>, &'static str> {
-    let mut content = Vec::new();
-    content.append(&mut format!("{}", data.len()).as_bytes().to_vec());
-    content.push(b':');
-    content.append(&mut data.clone());
-    Ok(content)
-}
+    pub fn iter_list_mut(&mut self) -> Option<core::slice::IterMut<'_, BData>> {
+        self.as_list_mut().map(|list| list.iter_mut())
+    }
 
-fn stringify_list(data: &Vec<BData>) -> Result<Vec<u8>, &str> {
-    let mut content = Vec::new();
-    let mut err_str = "";
-    content.push(b'l');
-    if !data.iter().all(|x| match stringify(x).as_mut() {
-        Ok(s) => {
-            content.append(s);
-            return true;
+    /// 按键的顺序遍历字典, 可以修改值但不能修改键
+    pub fn iter_dict_mut(&mut self) -> Option<impl Iterator<Item = (&[u8], &mut BData)>> {
+        self.as_dict_mut()
+            .map(|dict| dict.iter_mut().map(|(k, v)| (k.as_slice(), v)))
+    }
+
+    /// 取出字符串, 类型不符时在 `Err` 中返回原来的数据
+    pub fn into_bytes(mut self) -> Result<Vec<u8>, BData> {
+        match &mut self {
+            BData::BString(s) => Ok(core::mem::take(s)),
+            _ => Err(self),
         }
-        Err(s) => {
-            err_str = s;
-            return false;
+    }
+
+    /// 取出 UTF-8 字符串, 类型不符与编码错误分别对应 `IntoError` 的两种情况
+    pub fn into_string(self) -> Result<String, IntoError> {
+        match self.into_bytes() {
+            Ok(s) => String::from_utf8(s).map_err(IntoError::InvalidUtf8),
+            Err(v) => Err(IntoError::NotString(v)),
         }
-    }) {
-        return Err(err_str);
     }
 
-    content.push(b'e');
-    Ok(content)
-}
+    pub fn into_number(self) -> Result<i64, BData> {
+        match self {
+            BData::Number(n) => Ok(n),
+            v => Err(v),
+        }
+    }
 
-fn stringify_dict(data: &BTreeMap<String, BData>) -> Result<Vec<u8>, &str> {
-    let mut content = Vec::new();
-    content.push(b'd');
-    let mut err_str = "";
-    if !data.iter().all(|x| {
-        let key = stringify_string(&x.0.as_bytes().to_vec());
-        match key {
-            Ok(mut s) => {
-                content.append(&mut s);
-            }
-            Err(s) => {
-                err_str = s;
-                return false;
-            }
+    pub fn into_list(mut self) -> Result<Vec<BData>, BData> {
+        match &mut self {
+            BData::List(list) => Ok(core::mem::take(list)),
+            _ => Err(self),
         }
+    }
 
-        let value = stringify(x.1);
-        match value {
-            Ok(mut s) => {
-                content.append(&mut s);
-            }
-            Err(s) => {
-                err_str = s;
-                return false;
-            }
-        };
-        return true;
-    }) {
-        return Err(err_str);
+    pub fn into_dict(mut self) -> Result<Map<Vec<u8>, BData>, BData> {
+        match &mut self {
+            BData::Dict(dict) => Ok(core::mem::take(dict)),
+            _ => Err(self),
+        }
     }
 
-    content.push(b'e');
-    Ok(content)
-}
+    pub fn is_string(&self) -> bool {
+        matches!(self, BData::BString(_))
+    }
 
-#[cfg(test)]
-mod test {
-    use super::BData;
-    use std::collections::BTreeMap;
+    /// `Number` 与 `BigNumber` 都是整数
+    pub fn is_number(&self) -> bool {
+        matches!(self, BData::Number(_) | BData::BigNumber(_))
+    }
 
-    fn parse_bstring(s: &str) -> Result<String, &str> {
-        let v = super::parse(&s.as_bytes().to_vec());
-        if let Ok(BData::BString(data)) = v {
-            Ok(String::from_utf8(data).unwrap())
-        } else {
-            Err("err")
+    pub fn is_list(&self) -> bool {
+        matches!(self, BData::List(_))
+    }
+
+    pub fn is_dict(&self) -> bool {
+        matches!(self, BData::Dict(_))
+    }
+
+    /// 数据的类型, `BigNumber` 与 `Number` 同为 `BKind::Number`
+    pub fn kind(&self) -> BKind {
+        match self {
+            BData::BString(_) => BKind::Bytes,
+            BData::Number(_) | BData::BigNumber(_) => BKind::Number,
+            BData::List(_) => BKind::List,
+            BData::Dict(_) => BKind::Dict,
         }
     }
 
-    #[test]
-    fn parse_bstring_test() {
-        assert_eq!(parse_bstring("3:abc"), Ok("abc".to_string()));
-        assert_eq!(parse_bstring("3:ab"), Err("err"));
-        assert_eq!(parse_bstring("3:abcd"), Ok("abc".to_string()));
-        assert_eq!(parse_bstring("0:"), Ok("".to_string()));
-        assert_eq!(parse_bstring("-1:"), Err("err"));
+    /// 类型的名称, 用于错误信息, 如 `"expected dict, got number"`.
+    ///
+    /// 依次为 `"bytes"`, `"number"`, `"big number"`, `"list"` 与 `"dict"`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            BData::BString(_) => "bytes",
+            BData::Number(_) => "number",
+            BData::BigNumber(_) => "big number",
+            BData::List(_) => "list",
+            BData::Dict(_) => "dict",
+        }
     }
 
-    fn parse_num(s: &str) -> Result<i32, &str> {
-        let v = super::parse(&s.as_bytes().to_vec());
-        if let Ok(BData::Number(data)) = v {
-            Ok(data)
-        } else {
-            Err("err")
+    /// 不同类型之间比较时的顺序
+    fn rank(&self) -> u8 {
+        match self {
+            BData::Number(_) => 0,
+            BData::BigNumber(_) => 1,
+            BData::BString(_) => 2,
+            BData::List(_) => 3,
+            BData::Dict(_) => 4,
         }
     }
+}
 
-    #[test]
-    fn parse_num_test() {
-        assert_eq!(parse_num("i32e"), Ok(32));
-        assert_eq!(parse_num("i-32e"), Ok(-32));
-        assert_eq!(parse_num("i0e"), Ok(0));
-        assert_eq!(parse_num("i3.2e"), Err("err"));
-        assert_eq!(
-            parse_num(&format!("i{}e", i64::MAX).to_string()),
-            Err("err")
-        );
+/// 取出字典中键为 `key` 的值.
+///
+/// 不是字典或键不存在时 panic, 不希望 panic 时请使用 `as_dict` 或 `get_path`
+impl Index<&str> for BData {
+    type Output = BData;
+
+    fn index(&self, key: &str) -> &BData {
+        match self {
+            BData::Dict(dict) => match dict.get(key.as_bytes()) {
+                Some(v) => v,
+                None => panic!("no key {:?} in dict", key),
+            },
+            v => panic!("cannot index {} with key {:?}", v.type_name(), key),
+        }
     }
+}
 
-    fn parse_list(s: &str) -> Result<Vec<BData>, &str> {
-        let v = super::parse(&s.as_bytes().to_vec());
-        if let Ok(BData::List(rc)) = v {
-            Ok(rc)
-        } else {
-            Err("err")
+/// 取出列表中的第 `index` 个元素.
+///
+/// 不是列表或下标越界时 panic
+impl Index<usize> for BData {
+    type Output = BData;
+
+    fn index(&self, index: usize) -> &BData {
+        match self {
+            BData::List(list) => match list.get(index) {
+                Some(v) => v,
+                None => panic!(
+                    "index {} out of range for list of length {}",
+                    index,
+                    list.len()
+                ),
+            },
+            v => panic!("cannot index {} with {}", v.type_name(), index),
         }
     }
+}
 
-    fn parse_list_check<'b>(s: &'static str, check: &Vec<BData>) {
-        let v = parse_list(s);
-        match v {
-            Ok(rc) => {
-                let mut ch = check.iter();
-                for e in rc.iter() {
-                    if let Some(data) = ch.next() {
-                        assert_eq!(data, e);
-                    } else {
-                        assert!(false);
-                    }
+/// 键不存在时插入一个空字典, 因此可以直接赋值或继续向下创建.
+///
+/// 不是字典时 panic
+impl IndexMut<&str> for BData {
+    fn index_mut(&mut self, key: &str) -> &mut BData {
+        match self {
+            BData::Dict(dict) => dict
+                .entry(key.as_bytes().to_vec())
+                .or_insert_with(|| BData::Dict(Map::new())),
+            v => panic!("cannot index {} with key {:?}", v.type_name(), key),
+        }
+    }
+}
+
+/// 不是列表或下标越界时 panic
+impl IndexMut<usize> for BData {
+    fn index_mut(&mut self, index: usize) -> &mut BData {
+        match self {
+            BData::List(list) => {
+                let len = list.len();
+                match list.get_mut(index) {
+                    Some(v) => v,
+                    None => panic!("index {} out of range for list of length {}", index, len),
                 }
             }
-            Err(_) => assert!(false),
-        };
+            v => panic!("cannot index {} with {}", v.type_name(), index),
+        }
     }
+}
 
-    #[test]
-    fn parse_list_test() {
-        parse_list_check("le", &vec![]);
-        parse_list_check("l3:abce", &vec![BData::BString("abc".as_bytes().to_vec())]);
-        parse_list_check(
-            "l3:abc4:abcde",
-            &vec![
-                BData::BString("abc".as_bytes().to_vec()),
-                BData::BString("abcd".as_bytes().to_vec()),
-            ],
-        );
-        parse_list_check(
-            "l3:abci32el2:abee",
-            &vec![
-                BData::BString("abc".as_bytes().to_vec()),
-                BData::Number(32),
-                BData::List(vec![BData::BString("ab".as_bytes().to_vec())]),
-            ],
-        );
+/// `BData::pointer` 路径中的一段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BPathSeg<'a> {
+    /// 字典的键, 按原始字节匹配
+    Key(&'a [u8]),
+    /// 列表的下标
+    Index(usize),
+}
+
+impl<'a> From<&'a str> for BPathSeg<'a> {
+    fn from(key: &'a str) -> BPathSeg<'a> {
+        BPathSeg::Key(key.as_bytes())
     }
+}
 
-    fn parse_dict(s: &str) -> Result<BTreeMap<String, BData>, &str> {
-        let v = super::parse(&s.as_bytes().to_vec());
-        if let Ok(BData::Dict(map)) = v {
-            Ok(map)
-        } else {
-            Err("err")
+impl<'a> From<&'a [u8]> for BPathSeg<'a> {
+    fn from(key: &'a [u8]) -> BPathSeg<'a> {
+        BPathSeg::Key(key)
+    }
+}
+
+impl<'a> From<usize> for BPathSeg<'a> {
+    fn from(index: usize) -> BPathSeg<'a> {
+        BPathSeg::Index(index)
+    }
+}
+
+/// 将 `get_path` 的路径拆分为各段的原始字节
+fn split_path(path: &str) -> Vec<Vec<u8>> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+    let mut segs = vec![Vec::new()];
+    let mut bytes = path.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'.' | b'/' => segs.push(Vec::new()),
+            b'\\' => {
+                let last = segs.last_mut().unwrap();
+                last.push(bytes.next().unwrap_or(b'\\'));
+            }
+            _ => segs.last_mut().unwrap().push(b),
         }
     }
+    segs
+}
 
-    fn parse_dict_check(s: &str, map: &BTreeMap<String, BData>) {
-        let data = parse_dict(s);
+/// 列表的下标只接受十进制数字
+fn parse_index(seg: &[u8]) -> Option<usize> {
+    if seg.is_empty() || !seg.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    core::str::from_utf8(seg).ok()?.parse().ok()
+}
 
-        let m = data.expect("parse dict failed");
+/// 将 `overlay` 合并到 `base` 中.
+///
+/// 两者都是字典时逐个合并键值对, 同一个键的值继续按此规则合并, 只在一方存在的键保留.
+/// 其他情况 (包括两者都是列表) 用 `overlay` 整体替换 `base`, 列表不会拼接.
+/// 与 `BData::merge` 使用默认的 `MergeStrategy` 相同, 但 `base` 不必是字典
+pub fn merge(base: &mut BData, overlay: BData) {
+    merge_with(base, overlay, MergeStrategy::default());
+}
 
-        assert_eq!(m.len(), map.len());
-        m.iter().for_each(|x| {
-            assert_eq!(map.contains_key(x.0), true);
-            assert_eq!(x.1, map.get(x.0).unwrap());
-        });
+fn merge_with(base: &mut BData, mut overlay: BData, strategy: MergeStrategy) {
+    match (base, &mut overlay) {
+        (BData::Dict(base), BData::Dict(overlay)) => {
+            for (k, v) in core::mem::take(overlay) {
+                match base.entry(k) {
+                    Entry::Occupied(mut e) => merge_with(e.get_mut(), v, strategy),
+                    Entry::Vacant(e) => {
+                        e.insert(v);
+                    }
+                }
+            }
+        }
+        (BData::List(base), BData::List(overlay)) if strategy.lists == ListPolicy::Concat => {
+            base.append(overlay);
+        }
+        (base, _) => {
+            if strategy.conflicts == ConflictPolicy::OverwriteWithOther {
+                *base = overlay;
+            }
+        }
     }
+}
 
-    #[test]
-    fn parse_dict_test() {
-        parse_dict_check("de", &BTreeMap::new());
-        let source = "d2:k13:abce";
-        let mut m = BTreeMap::new();
-        m.insert("k1".to_string(), BData::BString("abc".as_bytes().to_vec()));
-        parse_dict_check(source, &m);
+/// `BData::merge` 的合并方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeStrategy {
+    /// 同一个键的值不都是字典时保留哪一个, 默认使用另一方的值
+    pub conflicts: ConflictPolicy,
+    /// 同一个键的值都是列表时的处理方式, 默认整体替换
+    pub lists: ListPolicy,
+}
 
-        let mut m = BTreeMap::new();
-        let source = "d2:k13:abc2:k2l3:defi-23eee";
-        m.insert("k1".to_string(), BData::BString("abc".as_bytes().to_vec()));
-        let mut k2_list = Vec::new();
-        k2_list.push(BData::BString("def".as_bytes().to_vec()));
-        k2_list.push(BData::Number(-23));
-        m.insert("k2".to_string(), BData::List(k2_list));
-        parse_dict_check(source, &m);
+/// 合并时同一个键的值发生冲突时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// 使用另一方的值
+    #[default]
+    OverwriteWithOther,
+    /// 保留原来的值
+    KeepSelf,
+}
+
+/// 合并时两个列表的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListPolicy {
+    /// 作为冲突处理, 由 `ConflictPolicy` 决定保留哪一个
+    #[default]
+    Replace,
+    /// 将另一方的元素追加到原来的列表之后
+    Concat,
+}
+
+/// 不同类型之间的顺序为 整数 (`Number` 与 `BigNumber`) `< BString < List < Dict`.
+///
+/// 整数按数值比较, `Number` 与 `BigNumber` 可以相互比较: 先比较符号, 再比较去掉前导零后的位数,
+/// 最后逐位比较, 负数的结果相反. 数值相同时 `Number` 排在 `BigNumber` 之前,
+/// 两个 `BigNumber` 再按原始字节比较 (如 `05` 与 `5`). 内容不是数字的 `BigNumber`
+/// 同样按这一规则比较, 其中的字节视为数字.
+///
+/// 其他同类型之间: `BString` 按原始字节的字典序比较; `List` 按元素逐个比较;
+/// `Dict` 按键的顺序逐个比较键值对, 先比较键再比较值.
+/// 较短的序列是较长序列的前缀时较短的排在前面.
+impl Ord for BData {
+    fn cmp(&self, other: &BData) -> Ordering {
+        match (self, other) {
+            (BData::Number(a), BData::Number(b)) => a.cmp(b),
+            (BData::Number(_) | BData::BigNumber(_), BData::Number(_) | BData::BigNumber(_)) => {
+                let (mut a, mut b) = ([0; 20], [0; 20]);
+                cmp_integers(integer_digits(self, &mut a), integer_digits(other, &mut b))
+                    .then_with(|| self.rank().cmp(&other.rank()))
+                    .then_with(|| match (self, other) {
+                        (BData::BigNumber(a), BData::BigNumber(b)) => a.cmp(b),
+                        _ => Ordering::Equal,
+                    })
+            }
+            (BData::BString(a), BData::BString(b)) => a.cmp(b),
+            (BData::List(a), BData::List(b)) => a.cmp(b),
+            (BData::Dict(a), BData::Dict(b)) => map::sorted(a).cmp(map::sorted(b)),
+            _ => self.rank().cmp(&other.rank()),
+        }
     }
+}
 
-    fn assert_stringify(s: &str, assert_s: Vec<u8>) {
-        if let Ok(data) = super::parse(&s.as_bytes().to_vec()) {
-            let stringify = super::stringify(&data);
-            println!("parse: {}", s);
-            if let Ok(st) = stringify {
-                assert_eq!(st, assert_s);
+/// 整数的符号以及去掉前导零的数字, 零没有数字且不是负数. `buf` 用于保存 `Number` 的数字
+fn integer_digits<'a>(data: &'a BData, buf: &'a mut [u8; 20]) -> (bool, &'a [u8]) {
+    match data {
+        BData::Number(n) => {
+            let mut rest = n.unsigned_abs();
+            let mut pos = buf.len();
+            while rest > 0 {
+                pos -= 1;
+                buf[pos] = b'0' + (rest % 10) as u8;
+                rest /= 10;
+            }
+            (*n < 0, &buf[pos..])
+        }
+        BData::BigNumber(num) => {
+            let (negative, digits) = match num.strip_prefix(b"-") {
+                Some(digits) => (true, digits),
+                None => (false, &num[..]),
+            };
+            let start = digits
+                .iter()
+                .position(|c| *c != b'0')
+                .unwrap_or(digits.len());
+            let digits = &digits[start..];
+            (negative && !digits.is_empty(), digits)
+        }
+        _ => unreachable!("not an integer"),
+    }
+}
+
+/// 按数值比较 `integer_digits` 的结果
+fn cmp_integers(a: (bool, &[u8]), b: (bool, &[u8])) -> Ordering {
+    let magnitude = |x: &[u8], y: &[u8]| x.len().cmp(&y.len()).then_with(|| x.cmp(y));
+    match (a.0, b.0) {
+        (false, false) => magnitude(a.1, b.1),
+        (true, true) => magnitude(b.1, a.1),
+        (negative, _) => {
+            if negative {
+                Ordering::Less
             } else {
-                panic!("stringify error!");
+                Ordering::Greater
             }
-        } else {
-            panic!("parse error!");
+        }
+    }
+}
+
+/// 字典按键的顺序计算, 与 `Eq` 一致
+impl Hash for BData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            BData::BString(s) | BData::BigNumber(s) => s.hash(state),
+            BData::Number(n) => n.hash(state),
+            BData::List(list) => list.hash(state),
+            BData::Dict(dict) => {
+                dict.len().hash(state);
+                map::sorted(dict).for_each(|entry| entry.hash(state));
+            }
+        }
+    }
+}
+
+impl PartialOrd for BData {
+    fn partial_cmp(&self, other: &BData) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 默认值为空字符串 `BString(vec![])`, 不会分配内存, 编码为 `0:`
+impl Default for BData {
+    fn default() -> BData {
+        BData::BString(Vec::new())
+    }
+}
+
+/// 逐层释放嵌套的容器, 嵌套很深的数据在小栈上释放也不会栈溢出
+impl Drop for BData {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        take_containers(self, &mut stack);
+        while let Some(mut data) = stack.pop() {
+            take_containers(&mut data, &mut stack);
+        }
+    }
+}
+
+/// 将 `data` 中非空的容器移到 `stack` 中, 剩下的元素可以直接释放
+fn take_containers(data: &mut BData, stack: &mut Vec<BData>) {
+    let is_nested = |v: &BData| match v {
+        BData::List(list) => !list.is_empty(),
+        BData::Dict(dict) => !dict.is_empty(),
+        _ => false,
+    };
+    match data {
+        BData::List(list) => stack.extend(
+            list.iter_mut()
+                .filter(|v| is_nested(v))
+                .map(core::mem::take),
+        ),
+        BData::Dict(dict) => stack.extend(
+            dict.values_mut()
+                .filter(|v| is_nested(v))
+                .map(core::mem::take),
+        ),
+        _ => {}
+    }
+}
+
+/// 输出 bencode 编码, 不是合法 UTF-8 的字节会被替换为 `U+FFFD`.
+///
+/// 不合法的 `BigNumber` 原样输出在 `i` 与 `e` 之间, 不会返回错误.
+/// 需要准确的编码时使用 `stringify`.
+impl fmt::Display for BData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_data(self, &mut FmtOutput(f), &EncodeOptions::default())
+    }
+}
+
+/// 字符串以 `BString("abc")` 的形式输出, 不是 UTF-8 或含有控制字符时以
+/// `BString(hex: 6162ff, len: 3)` 的形式输出, 过长的内容会被截断
+impl fmt::Debug for BData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BData::BString(s) => f.debug_tuple("BString").field(&DebugBytes(s)).finish(),
+            BData::Number(n) => f.debug_tuple("Number").field(n).finish(),
+            BData::BigNumber(n) => f.debug_tuple("BigNumber").field(&DebugBytes(n)).finish(),
+            BData::List(list) => f.debug_tuple("List").field(list).finish(),
+            BData::Dict(dict) => f.debug_tuple("Dict").field(&DebugDict(dict)).finish(),
+        }
+    }
+}
+
+/// `Debug` 中字符串最多显示的字节数
+const DEBUG_PREVIEW: usize = 64;
+
+/// 以 `Debug` 格式输出字符串的内容
+pub(crate) struct DebugBytes<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Debug for DebugBytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = self.0;
+        let mut end = s.len().min(DEBUG_PREVIEW);
+        match core::str::from_utf8(s) {
+            Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                write!(f, "{:?}", &text[..end])?;
+                if end < s.len() {
+                    write!(f, "…, len: {}", s.len())?;
+                }
+                Ok(())
+            }
+            _ => {
+                f.write_str("hex: ")?;
+                for b in &s[..end] {
+                    write!(f, "{:02x}", b)?;
+                }
+                if end < s.len() {
+                    f.write_str("…")?;
+                }
+                write!(f, ", len: {}", s.len())
+            }
+        }
+    }
+}
+
+/// 以 `Debug` 格式输出字典, 键的格式与字符串相同
+pub(crate) struct DebugDict<'a, K, V>(pub(crate) &'a Map<K, V>);
+
+impl<K: AsRef<[u8]>, V: fmt::Debug> fmt::Debug for DebugDict<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(k, v)| (DebugBytes(k.as_ref()), v)))
+            .finish()
+    }
+}
+
+impl From<&str> for BData {
+    fn from(s: &str) -> BData {
+        BData::BString(s.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for BData {
+    fn from(s: String) -> BData {
+        BData::BString(s.into_bytes())
+    }
+}
+
+impl From<&[u8]> for BData {
+    fn from(s: &[u8]) -> BData {
+        BData::BString(s.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for BData {
+    fn from(s: Vec<u8>) -> BData {
+        BData::BString(s)
+    }
+}
+
+impl From<i64> for BData {
+    fn from(n: i64) -> BData {
+        BData::Number(n)
+    }
+}
+
+impl From<i32> for BData {
+    fn from(n: i32) -> BData {
+        BData::Number(n.into())
+    }
+}
+
+impl From<Vec<BData>> for BData {
+    fn from(list: Vec<BData>) -> BData {
+        BData::List(list)
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, BData>> for BData {
+    fn from(dict: BTreeMap<Vec<u8>, BData>) -> BData {
+        dict.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "preserve-order")]
+impl From<Map<Vec<u8>, BData>> for BData {
+    fn from(dict: Map<Vec<u8>, BData>) -> BData {
+        BData::Dict(dict)
+    }
+}
+
+impl From<BTreeMap<String, BData>> for BData {
+    fn from(dict: BTreeMap<String, BData>) -> BData {
+        dict.into_iter().collect()
+    }
+}
+
+/// 构建列表
+impl FromIterator<BData> for BData {
+    fn from_iter<I: IntoIterator<Item = BData>>(iter: I) -> BData {
+        BData::List(iter.into_iter().collect())
+    }
+}
+
+/// 构建字典, 重复的键保留最后一个值
+impl FromIterator<(Vec<u8>, BData)> for BData {
+    fn from_iter<I: IntoIterator<Item = (Vec<u8>, BData)>>(iter: I) -> BData {
+        BData::Dict(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<(String, BData)> for BData {
+    fn from_iter<I: IntoIterator<Item = (String, BData)>>(iter: I) -> BData {
+        iter.into_iter().map(|(k, v)| (k.into_bytes(), v)).collect()
+    }
+}
+
+impl<'a> FromIterator<(&'a str, BData)> for BData {
+    fn from_iter<I: IntoIterator<Item = (&'a str, BData)>>(iter: I) -> BData {
+        iter.into_iter()
+            .map(|(k, v)| (k.as_bytes().to_vec(), v))
+            .collect()
+    }
+}
+
+/// 字典中一个键的位置, 由 `BData::entry` 返回
+#[derive(Debug)]
+pub struct BEntry<'a>(Entry<'a, Vec<u8>, BData>);
+
+impl<'a> BEntry<'a> {
+    pub fn key(&self) -> &[u8] {
+        self.0.key()
+    }
+
+    /// 键不存在时插入 `default`, 返回值的可变引用
+    pub fn or_insert<V: Into<BData>>(self, default: V) -> &'a mut BData {
+        self.0.or_insert_with(|| default.into())
+    }
+
+    /// 键不存在时插入 `f` 的返回值
+    pub fn or_insert_with<F: FnOnce() -> BData>(self, f: F) -> &'a mut BData {
+        self.0.or_insert_with(f)
+    }
+
+    /// 键不存在时插入空字典
+    pub fn or_insert_dict(self) -> &'a mut BData {
+        self.or_insert_with(|| BData::Dict(Map::new()))
+    }
+
+    /// 键不存在时插入空列表
+    pub fn or_insert_list(self) -> &'a mut BData {
+        self.or_insert_with(|| BData::List(Vec::new()))
+    }
+
+    /// 键存在时对值调用 `f`
+    pub fn and_modify<F: FnOnce(&mut BData)>(self, f: F) -> BEntry<'a> {
+        BEntry(self.0.and_modify(f))
+    }
+
+    /// 内部的 `Entry`, 用于区分键是否已经存在
+    pub fn into_entry(self) -> Entry<'a, Vec<u8>, BData> {
+        self.0
+    }
+}
+
+/// 依次添加键值对构建字典.
+///
+/// ```
+/// use fraux_rs::DictBuilder;
+/// let info = DictBuilder::new().entry("name", "a.txt").entry("length", 5).build();
+/// let data = DictBuilder::new().entry("info", info).build();
+/// assert_eq!(fraux_rs::stringify(&data).unwrap(), b"d4:infod6:lengthi5e4:name5:a.txtee");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DictBuilder {
+    dict: Map<Vec<u8>, BData>,
+}
+
+impl DictBuilder {
+    pub fn new() -> DictBuilder {
+        DictBuilder::default()
+    }
+
+    /// 添加一个键值对, 键已存在时替换原来的值
+    pub fn entry<K: Into<Vec<u8>>, V: Into<BData>>(mut self, key: K, value: V) -> DictBuilder {
+        self.dict.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> BData {
+        BData::Dict(self.dict)
+    }
+}
+
+impl TryFrom<BData> for i64 {
+    type Error = TryFromError;
+
+    fn try_from(data: BData) -> Result<i64, TryFromError> {
+        i64::try_from(&data)
+    }
+}
+
+impl TryFrom<&BData> for i64 {
+    type Error = TryFromError;
+
+    fn try_from(data: &BData) -> Result<i64, TryFromError> {
+        data.as_number()
+            .ok_or_else(|| TryFromError::wrong_type("number", data))
+    }
+}
+
+impl TryFrom<BData> for Vec<u8> {
+    type Error = TryFromError;
+
+    fn try_from(data: BData) -> Result<Vec<u8>, TryFromError> {
+        data.into_bytes()
+            .map_err(|v| TryFromError::wrong_type("bytes", &v))
+    }
+}
+
+impl TryFrom<&BData> for Vec<u8> {
+    type Error = TryFromError;
+
+    fn try_from(data: &BData) -> Result<Vec<u8>, TryFromError> {
+        data.as_bytes()
+            .map(|s| s.to_vec())
+            .ok_or_else(|| TryFromError::wrong_type("bytes", data))
+    }
+}
+
+impl TryFrom<BData> for String {
+    type Error = TryFromError;
+
+    fn try_from(data: BData) -> Result<String, TryFromError> {
+        String::from_utf8(Vec::try_from(data)?).map_err(|_| TryFromError::InvalidUtf8)
+    }
+}
+
+impl TryFrom<&BData> for String {
+    type Error = TryFromError;
+
+    fn try_from(data: &BData) -> Result<String, TryFromError> {
+        String::from_utf8(Vec::try_from(data)?).map_err(|_| TryFromError::InvalidUtf8)
+    }
+}
+
+/// 列表逐个转换元素, 出错时返回 `TryFromError::Element`
+impl<T> TryFrom<BData> for Vec<T>
+where
+    T: TryFrom<BData>,
+    T::Error: Into<TryFromError>,
+{
+    type Error = TryFromError;
+
+    fn try_from(data: BData) -> Result<Vec<T>, TryFromError> {
+        match data.into_list() {
+            Ok(list) => list
+                .into_iter()
+                .enumerate()
+                .map(|(index, v)| {
+                    T::try_from(v).map_err(|e| TryFromError::Element {
+                        index,
+                        source: Box::new(e.into()),
+                    })
+                })
+                .collect(),
+            Err(v) => Err(TryFromError::wrong_type("list", &v)),
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&'a BData> for Vec<T>
+where
+    T: TryFrom<&'a BData>,
+    T::Error: Into<TryFromError>,
+{
+    type Error = TryFromError;
+
+    fn try_from(data: &'a BData) -> Result<Vec<T>, TryFromError> {
+        let list = data
+            .as_list()
+            .ok_or_else(|| TryFromError::wrong_type("list", data))?;
+        list.iter()
+            .enumerate()
+            .map(|(index, v)| {
+                T::try_from(v).map_err(|e| TryFromError::Element {
+                    index,
+                    source: Box::new(e.into()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 字典的键必须是合法的 UTF-8
+impl TryFrom<BData> for BTreeMap<String, BData> {
+    type Error = TryFromError;
+
+    fn try_from(data: BData) -> Result<BTreeMap<String, BData>, TryFromError> {
+        match data.into_dict() {
+            Ok(dict) => dict
+                .into_iter()
+                .map(|(k, v)| {
+                    let k = String::from_utf8(k).map_err(|_| TryFromError::InvalidUtf8)?;
+                    Ok((k, v))
+                })
+                .collect(),
+            Err(v) => Err(TryFromError::wrong_type("dict", &v)),
+        }
+    }
+}
+
+impl TryFrom<&BData> for BTreeMap<String, BData> {
+    type Error = TryFromError;
+
+    fn try_from(data: &BData) -> Result<BTreeMap<String, BData>, TryFromError> {
+        let dict = data
+            .as_dict()
+            .ok_or_else(|| TryFromError::wrong_type("dict", data))?;
+        dict.iter()
+            .map(|(k, v)| {
+                let k = core::str::from_utf8(k).map_err(|_| TryFromError::InvalidUtf8)?;
+                Ok((k.to_string(), v.clone()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseErr {
+    /// 读到了不符合格式的字节, `expected` 为该位置期望的内容
+    UnexpectedByte {
+        found: u8,
+        expected: &'static str,
+        offset: usize,
+    },
+    /// 数据缺失, `offset` 为输入结束的位置
+    UnexpectedEof {
+        expected: &'static str,
+        offset: usize,
+    },
+    /// 整数格式错误, 如空整数, `-0` 以及严格模式下的前导零. `offset` 为整数的位置
+    InvalidInteger { literal: String, offset: usize },
+    /// 整数超出 `i64` 的范围, 或字符串长度超出 `usize` 的范围
+    IntegerOverflow { literal: String, offset: usize },
+    /// 字典的键不是字符串
+    NonStringDictKey { offset: usize },
+    /// 转换中出现的异常
+    ParseFailure(Box<dyn Error + Send + Sync>),
+    /// 读取数据时出现的 IO 异常
+    #[cfg(feature = "std")]
+    IoError(io::Error),
+    /// 数据结尾之后仍有多余的字节, `offset` 为第一个多余字节的位置
+    TrailingData { offset: usize },
+    /// 嵌套层数超过限制, `offset` 为超出限制的容器的位置
+    DepthLimitExceeded { offset: usize },
+    /// 字符串声明的长度超过限制
+    StringTooLong {
+        offset: usize,
+        declared: usize,
+        limit: usize,
+    },
+    /// 列表或字典的元素个数超过限制, `offset` 为超出限制的元素的位置
+    TooManyItems { offset: usize, limit: usize },
+    /// 数据的总长度超过限制, `offset` 为超出限制的 token 的位置
+    SizeLimitExceeded { offset: usize, limit: usize },
+    /// 字典中出现重复的键, `offset` 为重复的键的位置
+    DuplicateKey { key: Vec<u8>, offset: usize },
+    /// 字典的键没有按字节升序排列, `offset` 为顺序错误的键的位置
+    UnsortedKeys { offset: usize },
+}
+
+impl ParseErr {
+    /// 出错的位置. `ParseFailure` 与 `IoError` 没有位置信息, 返回 `None`
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            ParseErr::UnexpectedByte { offset, .. }
+            | ParseErr::UnexpectedEof { offset, .. }
+            | ParseErr::InvalidInteger { offset, .. }
+            | ParseErr::IntegerOverflow { offset, .. }
+            | ParseErr::NonStringDictKey { offset }
+            | ParseErr::TrailingData { offset }
+            | ParseErr::DepthLimitExceeded { offset }
+            | ParseErr::StringTooLong { offset, .. }
+            | ParseErr::TooManyItems { offset, .. }
+            | ParseErr::SizeLimitExceeded { offset, .. }
+            | ParseErr::DuplicateKey { offset, .. }
+            | ParseErr::UnsortedKeys { offset } => Some(*offset),
+            ParseErr::ParseFailure(_) => None,
+            #[cfg(feature = "std")]
+            ParseErr::IoError(_) => None,
+        }
+    }
+
+    /// 错误的大致类别, 便于在不关心细节时比较错误
+    pub fn kind(&self) -> ParseErrKind {
+        match self {
+            ParseErr::UnexpectedByte { .. }
+            | ParseErr::InvalidInteger { .. }
+            | ParseErr::NonStringDictKey { .. } => ParseErrKind::Syntax,
+            ParseErr::UnexpectedEof { .. } => ParseErrKind::UnexpectedEof,
+            ParseErr::IntegerOverflow { .. } | ParseErr::ParseFailure(_) => {
+                ParseErrKind::Conversion
+            }
+            #[cfg(feature = "std")]
+            ParseErr::IoError(_) => ParseErrKind::Io,
+            ParseErr::TrailingData { .. } => ParseErrKind::TrailingData,
+            ParseErr::DepthLimitExceeded { .. }
+            | ParseErr::StringTooLong { .. }
+            | ParseErr::TooManyItems { .. }
+            | ParseErr::SizeLimitExceeded { .. } => ParseErrKind::LimitExceeded,
+            ParseErr::DuplicateKey { .. } => ParseErrKind::DuplicateKey,
+            ParseErr::UnsortedKeys { .. } => ParseErrKind::UnsortedKeys,
+        }
+    }
+}
+
+impl fmt::Display for ParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErr::UnexpectedByte {
+                found,
+                expected,
+                offset,
+            } => write!(
+                f,
+                "unexpected byte {:?} at byte {}, expected {}",
+                char::from(*found),
+                offset,
+                expected
+            ),
+            ParseErr::UnexpectedEof { expected, offset } => write!(
+                f,
+                "unexpected end of input at byte {}, expected {}",
+                offset, expected
+            ),
+            ParseErr::InvalidInteger { literal, offset } => {
+                write!(f, "invalid integer {:?} at byte {}", literal, offset)
+            }
+            ParseErr::IntegerOverflow { literal, offset } => {
+                write!(
+                    f,
+                    "integer {:?} at byte {} is out of range",
+                    literal, offset
+                )
+            }
+            ParseErr::NonStringDictKey { offset } => {
+                write!(f, "dictionary key at byte {} is not a string", offset)
+            }
+            ParseErr::ParseFailure(e) => write!(f, "invalid value: {}", e),
+            #[cfg(feature = "std")]
+            ParseErr::IoError(e) => write!(f, "io error: {}", e),
+            ParseErr::TrailingData { offset } => {
+                write!(f, "trailing data after value at byte {}", offset)
+            }
+            ParseErr::DepthLimitExceeded { offset } => {
+                write!(f, "nesting depth limit exceeded at byte {}", offset)
+            }
+            ParseErr::StringTooLong {
+                offset,
+                declared,
+                limit,
+            } => write!(
+                f,
+                "string at byte {} declares {} bytes, limit is {}",
+                offset, declared, limit
+            ),
+            ParseErr::TooManyItems { offset, limit } => write!(
+                f,
+                "container item limit {} exceeded at byte {}",
+                limit, offset
+            ),
+            ParseErr::SizeLimitExceeded { offset, limit } => {
+                write!(f, "input size limit {} exceeded at byte {}", limit, offset)
+            }
+            ParseErr::DuplicateKey { key, offset } => write!(
+                f,
+                "duplicate dictionary key {:?} at byte {}",
+                String::from_utf8_lossy(key),
+                offset
+            ),
+            ParseErr::UnsortedKeys { offset } => {
+                write!(f, "dictionary key out of order at byte {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for ParseErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseErr::ParseFailure(e) => Some(e.as_ref()),
+            #[cfg(feature = "std")]
+            ParseErr::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// 编码时出现的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// 该类型在 bencode 中没有对应的表示, 如浮点数与空值
+    UnsupportedType(&'static str),
+    /// 字典的键不是字符串
+    InvalidKey,
+    /// `BigNumber` 的内容不是合法的整数
+    InvalidBigNumber(Vec<u8>),
+    /// 自定义的错误
+    Custom(String),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::UnsupportedType(ty) => {
+                write!(f, "{} cannot be represented in bencode", ty)
+            }
+            EncodeError::InvalidKey => write!(f, "dictionary key is not a string"),
+            EncodeError::InvalidBigNumber(num) => {
+                write!(f, "invalid big integer {:?}", String::from_utf8_lossy(num))
+            }
+            EncodeError::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
+/// `BData::into_string` 失败的原因, 可以通过 `into_data` 取回原来的数据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntoError {
+    /// 不是字符串
+    NotString(BData),
+    /// 字符串不是合法的 UTF-8
+    InvalidUtf8(FromUtf8Error),
+}
+
+impl IntoError {
+    /// 转换前的数据
+    pub fn into_data(self) -> BData {
+        match self {
+            IntoError::NotString(data) => data,
+            IntoError::InvalidUtf8(e) => BData::BString(e.into_bytes()),
+        }
+    }
+}
+
+impl fmt::Display for IntoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntoError::NotString(_) => write!(f, "value is not a string"),
+            IntoError::InvalidUtf8(e) => write!(f, "string is not valid UTF-8: {}", e),
+        }
+    }
+}
+
+impl Error for IntoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IntoError::NotString(_) => None,
+            IntoError::InvalidUtf8(e) => Some(e),
+        }
+    }
+}
+
+/// `BData` 通过 `TryFrom` 转换为其他类型时的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryFromError {
+    /// 数据的类型不符, 如 `expected: "number", found: "list"`
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// 字符串或字典的键不是合法的 UTF-8
+    InvalidUtf8,
+    /// 列表中第 `index` 个元素转换失败
+    Element {
+        index: usize,
+        source: Box<TryFromError>,
+    },
+}
+
+impl TryFromError {
+    fn wrong_type(expected: &'static str, found: &BData) -> TryFromError {
+        TryFromError::WrongType {
+            expected,
+            found: found.type_name(),
+        }
+    }
+}
+
+impl fmt::Display for TryFromError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryFromError::WrongType { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            TryFromError::InvalidUtf8 => write!(f, "string is not valid UTF-8"),
+            TryFromError::Element { index, source } => {
+                write!(f, "list element {}: {}", index, source)
+            }
+        }
+    }
+}
+
+impl Error for TryFromError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TryFromError::Element { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
+
+/// 用于 `Vec<BData>` 等不会失败的元素转换
+impl From<Infallible> for TryFromError {
+    fn from(e: Infallible) -> TryFromError {
+        match e {}
+    }
+}
+
+/// `ParseErr` 的类别, 参见 `ParseErr::kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrKind {
+    /// 数据格式错误
+    Syntax,
+    /// 数据缺失
+    UnexpectedEof,
+    /// 数值转换失败
+    Conversion,
+    /// IO 异常
+    Io,
+    /// 数据之后有多余的字节
+    TrailingData,
+    /// 超出 `ParseOptions` 中的某项限制
+    LimitExceeded,
+    /// 字典中出现重复的键
+    DuplicateKey,
+    /// 字典的键顺序错误
+    UnsortedKeys,
+}
+
+/// 解析选项.
+///
+/// 除嵌套层数外其余限制默认不生效, 各项限制都在分配内存之前检查.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// 最大嵌套层数, 超出时返回 `ParseErr::DepthLimitExceeded`.
+    ///
+    /// 解析本身不使用递归, 但编码, 比较以及释放数据都会按层数递归, 因此不宜设置得过大
+    pub max_depth: usize,
+    /// 单个字符串的最大长度, 超出时返回 `ParseErr::StringTooLong`
+    pub max_string_len: usize,
+    /// 单个列表或字典的最大元素个数, 字典的一对键值算作一个元素.
+    /// 超出时返回 `ParseErr::TooManyItems`
+    pub max_items: usize,
+    /// 单个数据的最大总字节数, 超出时返回 `ParseErr::SizeLimitExceeded`.
+    /// `Parser` 连续解析多个数据时分别计算
+    pub max_total_bytes: usize,
+    /// 字典中出现重复的键时的处理方式, 默认返回 `ParseErr::DuplicateKey`
+    pub duplicate_keys: DuplicateKeyPolicy,
+    /// 是否要求字典的键按原始字节严格升序排列, 默认不要求.
+    /// 要求时顺序错误返回 `ParseErr::UnsortedKeys`
+    pub require_sorted_keys: bool,
+    /// 是否拒绝整数以及字符串长度中多余的前导零 (如 `i03e`, `03:abc`), 默认不拒绝.
+    /// 拒绝时返回 `ParseErr::InvalidInteger`
+    pub strict_integers: bool,
+    /// 超出 `i64` 范围的整数是否保存为 `BData::BigNumber`, 默认不保存,
+    /// 此时返回 `ParseErr::IntegerOverflow`
+    pub big_numbers: bool,
+    /// 是否忽略数据之后多余的字节, 默认不忽略, 此时返回 `ParseErr::TrailingData`.
+    /// 需要知道数据结尾的位置时使用 `parse_prefix`
+    pub allow_trailing_data: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_string_len: usize::MAX,
+            max_items: usize::MAX,
+            max_total_bytes: usize::MAX,
+            duplicate_keys: DuplicateKeyPolicy::Error,
+            require_sorted_keys: false,
+            strict_integers: false,
+            big_numbers: false,
+            allow_trailing_data: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// 严格模式, 只接受规范的编码: 键必须有序且不重复, 数字不能有前导零.
+    ///
+    /// 严格模式接受的数据重新编码后与原始字节完全相同.
+    /// 任何模式下 `i-0e`, `ie` 以及 `i+5e` 都是格式错误.
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            duplicate_keys: DuplicateKeyPolicy::Error,
+            require_sorted_keys: true,
+            strict_integers: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// 字典中出现重复的键时的处理方式, 对所有嵌套层级的字典生效
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// 保留最后一个值
+    KeepLast,
+    /// 保留第一个值
+    KeepFirst,
+    /// 返回 `ParseErr::DuplicateKey`
+    Error,
+}
+
+/// 由 token 构建的数据树的节点, `BData` 复制字符串, `BDataRef` 借用输入
+pub(crate) trait Node<'a>: Sized {
+    type Key: Ord + Hash + AsRef<[u8]>;
+
+    /// 字符串与整数
+    fn leaf(token: Token<'a>) -> Self;
+    fn list(list: Vec<Self>) -> Self;
+    fn dict(dict: Map<Self::Key, Self>) -> Self;
+    /// 字符串可以作为字典的键, 其他类型返回 `None`
+    fn into_key(self) -> Option<Self::Key>;
+}
+
+impl<'a> Node<'a> for BData {
+    type Key = Vec<u8>;
+
+    fn leaf(token: Token<'a>) -> BData {
+        match token {
+            Token::Int(n) => BData::Number(n),
+            Token::BigInt(n) => BData::BigNumber(n.to_vec()),
+            Token::Bytes(b) => BData::BString(b.to_vec()),
+            _ => unreachable!("container token is not a leaf"),
+        }
+    }
+
+    fn list(list: Vec<BData>) -> BData {
+        BData::List(list)
+    }
+
+    fn dict(dict: Map<Vec<u8>, BData>) -> BData {
+        BData::Dict(dict)
+    }
+
+    fn into_key(self) -> Option<Vec<u8>> {
+        self.into_bytes().ok()
+    }
+}
+
+/// 尚未结束的容器
+enum Frame<V, K> {
+    List(Vec<V>),
+    /// 字典以及已读取但还没有对应值的键
+    Dict(Map<K, V>, Option<K>),
+}
+
+/// 根据 token 构建数据树
+pub(crate) struct Builder<V = BData, K = Vec<u8>> {
+    stack: Vec<Frame<V, K>>,
+    duplicate_keys: DuplicateKeyPolicy,
+    require_sorted_keys: bool,
+}
+
+impl<V, K: Ord + Hash + AsRef<[u8]>> Builder<V, K> {
+    pub(crate) fn with_options(opts: &ParseOptions) -> Builder<V, K> {
+        Builder {
+            stack: Vec::new(),
+            duplicate_keys: opts.duplicate_keys,
+            require_sorted_keys: opts.require_sorted_keys,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.stack.clear();
+    }
+
+    /// 读入位于 `offset` 的 token, 最外层的数据完成时返回该数据
+    pub(crate) fn push<'a>(
+        &mut self,
+        offset: usize,
+        token: Token<'a>,
+    ) -> Result<Option<V>, ParseErr>
+    where
+        V: Node<'a, Key = K>,
+    {
+        let v = match token {
+            Token::ListStart => {
+                self.stack.push(Frame::List(Vec::new()));
+                return Ok(None);
+            }
+            Token::DictStart => {
+                self.stack.push(Frame::Dict(Map::new(), None));
+                return Ok(None);
+            }
+            Token::ListEnd | Token::DictEnd => match self.stack.pop() {
+                Some(Frame::List(list)) => V::list(list),
+                Some(Frame::Dict(map, _)) => V::dict(map),
+                None => {
+                    return Err(ParseErr::UnexpectedByte {
+                        found: b'e',
+                        expected: "value",
+                        offset,
+                    })
+                }
+            },
+            Token::Bytes(_) | Token::Int(_) | Token::BigInt(_) => V::leaf(token),
+        };
+
+        match self.stack.last_mut() {
+            None => return Ok(Some(v)),
+            Some(Frame::List(list)) => list.push(v),
+            Some(Frame::Dict(map, key)) => match key.take() {
+                Some(k) => match self.duplicate_keys {
+                    DuplicateKeyPolicy::KeepFirst => {
+                        map.entry(k).or_insert(v);
+                    }
+                    _ => {
+                        map.insert(k, v);
+                    }
+                },
+                None => match v.into_key() {
+                    Some(k)
+                        if self.duplicate_keys == DuplicateKeyPolicy::Error
+                            && map.contains_key(&k) =>
+                    {
+                        let key = k.as_ref().to_vec();
+                        return Err(ParseErr::DuplicateKey { key, offset });
+                    }
+                    // 已读取的键中最大的一个即为上一个键
+                    Some(k)
+                        if self.require_sorted_keys
+                            && map.keys().next_back().is_some_and(|last| k <= *last) =>
+                    {
+                        return Err(ParseErr::UnsortedKeys { offset });
+                    }
+                    Some(k) => *key = Some(k),
+                    None => return Err(ParseErr::NonStringDictKey { offset }),
+                },
+            },
+        }
+        Ok(None)
+    }
+}
+
+/// 解析 `src` 中的一个完整数据, 数据之后有多余字节时返回 `ParseErr::TrailingData`.
+///
+/// 使用默认的 `ParseOptions`: 拒绝多余字节与重复的键, 不要求键有序, 接受前导零,
+/// 嵌套层数不超过 `DEFAULT_MAX_DEPTH`. 需要其他规则时使用 `parse_with_options`,
+/// 需要忽略多余字节时请使用 `parse_prefix`.
+pub fn parse(src: &[u8]) -> Result<BData, ParseErr> {
+    parse_complete(src)
+}
+
+/// 与 `parse` 相同, 输入为字符串.
+///
+/// 字符串长度按字节计算, 因此 `"2:é"` 是合法的数据,
+/// 解析出的字符串同样是原始字节.
+///
+/// ```
+/// use fraux_rs::BData;
+/// assert_eq!(fraux_rs::parse_str("2:é").unwrap(), BData::BString("é".as_bytes().to_vec()));
+/// assert!(fraux_rs::parse_str("1:é").is_err());
+/// ```
+pub fn parse_str(src: &str) -> Result<BData, ParseErr> {
+    parse(src.as_bytes())
+}
+
+/// 从 `src` 的开头解析一个完整的数据, 同时返回其占用的字节数.
+///
+/// 数据之后的内容不会被检查, 由调用方自行处理.
+///
+/// ```
+/// let buf = b"d1:ai1ee\x00\x01\x02";
+/// let (_data, n) = fraux_rs::parse_prefix(buf).unwrap();
+/// let rest = &buf[n..];
+/// assert_eq!(rest, b"\x00\x01\x02");
+/// ```
+pub fn parse_prefix(src: &[u8]) -> Result<(BData, usize), ParseErr> {
+    parse_prefix_with(src, 0, &ParseOptions::default())
+}
+
+/// 从 `src[pos..]` 解析一个数据, 返回数据以及数据结尾在 `src` 中的位置
+fn parse_prefix_with<'a, V: Node<'a>>(
+    src: &'a [u8],
+    pos: usize,
+    opts: &ParseOptions,
+) -> Result<(V, usize), ParseErr> {
+    let mut tokens = Tokenizer::with_lexer(src, pos, Lexer::with_options(opts));
+    let mut builder = Builder::<V, V::Key>::with_options(opts);
+    for token in tokens.by_ref() {
+        let (offset, token) = token?;
+        if let Some(data) = builder.push(offset, token)? {
+            return Ok((data, tokens.offset()));
+        }
+    }
+    Err(ParseErr::UnexpectedEof {
+        expected: "value",
+        offset: src.len(),
+    })
+}
+
+/// 解析 `src` 中的一个完整数据, 数据之后有任何多余字节都视为错误.
+pub fn parse_complete(src: &[u8]) -> Result<BData, ParseErr> {
+    parse_complete_with(src, &ParseOptions::default())
+}
+
+fn parse_complete_with<'a, V: Node<'a>>(src: &'a [u8], opts: &ParseOptions) -> Result<V, ParseErr> {
+    let (data, consumed) = parse_prefix_with(src, 0, opts)?;
+    if consumed < src.len() && !opts.allow_trailing_data {
+        return Err(ParseErr::TrailingData { offset: consumed });
+    }
+    Ok(data)
+}
+
+/// 与 `parse` 相同, 但使用指定的解析选项.
+pub fn parse_with_options(src: &[u8], opts: &ParseOptions) -> Result<BData, ParseErr> {
+    parse_complete_with(src, opts)
+}
+
+/// 与 `parse` 相同, 但使用严格模式, 参见 `ParseOptions::strict`.
+pub fn parse_strict(src: &[u8]) -> Result<BData, ParseErr> {
+    parse_with_options(src, &ParseOptions::strict())
+}
+
+/// `validate` 中尚未结束的容器
+enum Level<'a> {
+    List,
+    /// `key` 为 true 时下一个元素应当是键, `last` 为上一个键
+    Dict {
+        key: bool,
+        last: Option<&'a [u8]>,
+    },
+}
+
+/// 检查 `src` 是否为严格模式下合法的完整数据, 不构建数据树.
+///
+/// 检查的规则与错误与 `parse_strict` 相同, 只为每层嵌套保存上一个键的位置
+pub fn validate(src: &[u8]) -> Result<(), ParseErr> {
+    validate_with(src, &ParseOptions::strict())
+}
+
+/// `src` 是否已经是规范的编码, 即重新编码后与原始字节完全相同.
+///
+/// 要求没有前导零与 `-0`, 字典的键有序且不重复, 数据之后没有多余字节.
+/// 与 `validate` 不同, 超出 `i64` 范围的整数同样可以是规范的编码.
+///
+/// 使用默认的解析选项, 嵌套超过 `DEFAULT_MAX_DEPTH` 层的数据同样返回 false,
+/// 需要区分时使用 `is_canonical_with`
+///
+/// ```
+/// assert!(fraux_rs::is_canonical(b"d1:ai1e1:bi99999999999999999999ee"));
+/// assert!(!fraux_rs::is_canonical(b"d1:bi1e1:ai2ee"));
+/// ```
+pub fn is_canonical(src: &[u8]) -> bool {
+    matches!(is_canonical_with(src, &ParseOptions::default()), Ok(true))
+}
+
+/// 与 `is_canonical` 相同, 但使用 `opts` 中的各项限制.
+///
+/// 规范编码的规则不受 `opts` 中其他选项的影响. 超出限制时返回 `ParseErrKind::LimitExceeded`
+/// 类的错误, 以便与不规范的编码区分
+///
+/// ```
+/// use fraux_rs::{ParseErrKind, ParseOptions};
+/// let src = [&[b'l'; 200][..], &[b'e'; 200][..]].concat();
+/// assert!(!fraux_rs::is_canonical(&src));
+/// let e = fraux_rs::is_canonical_with(&src, &ParseOptions::default()).unwrap_err();
+/// assert_eq!(e.kind(), ParseErrKind::LimitExceeded);
+/// let opts = ParseOptions { max_depth: 200, ..Default::default() };
+/// assert!(fraux_rs::is_canonical_with(&src, &opts).unwrap());
+/// ```
+pub fn is_canonical_with(src: &[u8], opts: &ParseOptions) -> Result<bool, ParseErr> {
+    let opts = ParseOptions {
+        duplicate_keys: DuplicateKeyPolicy::Error,
+        require_sorted_keys: true,
+        strict_integers: true,
+        big_numbers: true,
+        allow_trailing_data: false,
+        ..opts.clone()
+    };
+    match validate_with(src, &opts) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ParseErrKind::LimitExceeded => Err(e),
+        Err(_) => Ok(false),
+    }
+}
+
+fn validate_with(src: &[u8], opts: &ParseOptions) -> Result<(), ParseErr> {
+    let mut tokens = Tokenizer::with_lexer(src, 0, Lexer::with_options(opts));
+    let mut stack = Vec::new();
+    for token in tokens.by_ref() {
+        let (offset, token) = token?;
+        match (token, stack.last_mut()) {
+            (
+                Token::Bytes(k),
+                Some(Level::Dict {
+                    key: key @ true,
+                    last,
+                }),
+            ) => {
+                match last {
+                    Some(last) if k == *last => {
+                        let key = k.to_vec();
+                        return Err(ParseErr::DuplicateKey { key, offset });
+                    }
+                    Some(last) if k < *last => return Err(ParseErr::UnsortedKeys { offset }),
+                    _ => {}
+                }
+                *last = Some(k);
+                *key = false;
+                continue;
+            }
+            (Token::ListStart, _) => {
+                stack.push(Level::List);
+                continue;
+            }
+            (Token::DictStart, _) => {
+                stack.push(Level::Dict {
+                    key: true,
+                    last: None,
+                });
+                continue;
+            }
+            (Token::ListEnd, _) | (Token::DictEnd, _) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+        // 一个值结束, 所在字典的下一个元素为键
+        if let Some(Level::Dict { key, .. }) = stack.last_mut() {
+            *key = true;
+        }
+    }
+    if tokens.offset() < src.len() && !opts.allow_trailing_data {
+        return Err(ParseErr::TrailingData {
+            offset: tokens.offset(),
+        });
+    }
+    Ok(())
+}
+
+/// 与 `parse` 相同, 但使用指定的最大嵌套层数.
+///
+/// 其他解析函数使用的默认限制为 `DEFAULT_MAX_DEPTH`. 解析不可信的数据时可以调小该值.
+pub fn parse_with_limits(src: &[u8], max_depth: usize) -> Result<BData, ParseErr> {
+    let opts = ParseOptions {
+        max_depth,
+        ..Default::default()
+    };
+    parse_with_options(src, &opts)
+}
+
+/// 与 `parse` 相同, 同时返回每个数据在 `src` 中的位置.
+///
+/// 可以用于取得某个子数据的原始编码, 例如计算 torrent 文件的 info hash.
+pub fn parse_with_spans(src: &[u8]) -> Result<(BData, SpanTree), ParseErr> {
+    parse_with_spans_options(src, &ParseOptions::default())
+}
+
+/// 与 `parse_with_spans` 相同, 但使用指定的解析选项.
+pub fn parse_with_spans_options(
+    src: &[u8],
+    opts: &ParseOptions,
+) -> Result<(BData, SpanTree), ParseErr> {
+    let mut tokens = Tokenizer::with_lexer(src, 0, Lexer::with_options(opts));
+    let mut builder = Builder::with_options(opts);
+    let mut spans = SpanBuilder::with_options(opts);
+    while let Some(token) = tokens.next() {
+        let (offset, token) = token?;
+        let data = builder.push(offset, token)?;
+        let tree = spans.push(offset, tokens.offset(), token);
+        if let (Some(data), Some(tree)) = (data, tree) {
+            if tokens.offset() < src.len() && !opts.allow_trailing_data {
+                return Err(ParseErr::TrailingData {
+                    offset: tokens.offset(),
+                });
+            }
+            return Ok((data, tree));
+        }
+    }
+    Err(ParseErr::UnexpectedEof {
+        expected: "value",
+        offset: src.len(),
+    })
+}
+
+/// 依次解析 `src` 中首尾相连的多个数据, 参见 `values`.
+pub fn parse_all(src: &[u8]) -> Result<Vec<BData>, ParseErr> {
+    values(src).collect()
+}
+
+/// 返回依次解析 `src` 中首尾相连的多个数据的迭代器.
+///
+/// 输入为空时不产生任何数据. 遇到错误时产生该错误并结束,
+/// 此时可以通过 `Values::offset` 获得出错的数据的起始位置.
+pub fn values(src: &[u8]) -> Values<'_> {
+    Values {
+        src,
+        pos: 0,
+        done: false,
+    }
+}
+
+/// `values` 返回的迭代器
+pub struct Values<'a> {
+    src: &'a [u8],
+    pos: usize,
+    done: bool,
+}
+
+impl Values<'_> {
+    /// 下一个数据的起始位置, 出错后为出错的数据的起始位置
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+}
+
+impl Iterator for Values<'_> {
+    type Item = Result<BData, ParseErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.pos >= self.src.len() {
+            return None;
+        }
+
+        match parse_prefix_with(self.src, self.pos, &ParseOptions::default()) {
+            Ok((data, end)) => {
+                self.pos = end;
+                Some(Ok(data))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) const READER_BUF_SIZE: usize = 4096;
+
+/// 从 `io::Read` 中解析数据, 不需要事先将全部内容读入内存.
+///
+/// 数据结束后不会再读取, 可以用于 `TcpStream` 等不会主动结束的 reader. 数据结尾之后
+/// 已经读入缓冲区的字节视为多余的数据, 返回 `ParseErr::TrailingData`, 因此 reader 中
+/// 首尾相连的多个数据需要使用 `Parser` 逐个解析.
+#[cfg(feature = "std")]
+pub fn parse_reader<R: Read>(mut r: R) -> Result<BData, ParseErr> {
+    let mut parser = Parser::new();
+    let mut buf = [0; READER_BUF_SIZE];
+    // 已经送入解析器的字节数
+    let mut total = 0;
+    loop {
+        let n = read_retry(&mut r, &mut buf)?;
+        if n == 0 {
+            parser.finish()?;
+            // 没有读到任何数据
+            return Err(ParseErr::UnexpectedEof {
+                expected: "value",
+                offset: 0,
+            });
+        }
+        total += n;
+        if let Some((data, consumed)) = parser.push(&buf[..n])? {
+            let offset = total - n + consumed;
+            if consumed < n {
+                return Err(ParseErr::TrailingData { offset });
+            }
+            return Ok(data);
+        }
+    }
+}
+
+/// 读取一次, 忽略 `io::ErrorKind::Interrupted`
+#[cfg(feature = "std")]
+fn read_retry<R: Read>(r: &mut R, buf: &mut [u8]) -> Result<usize, ParseErr> {
+    loop {
+        match r.read(buf) {
+            Ok(n) => return Ok(n),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(ParseErr::IoError(e)),
+        }
+    }
+}
+
+/// 读取并解析整个文件, 文件内容必须是一个完整的数据.
+#[cfg(feature = "std")]
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<BData, ParseErr> {
+    let src = fs::read(path).map_err(ParseErr::IoError)?;
+    parse(&src)
+}
+
+/// 将数据编码后写入文件.
+///
+/// 先写入同一目录下的临时文件, 完成后再重命名为 `path`, 写入中途出错或崩溃不会破坏原有的文件.
+/// 编码错误与 `to_writer` 相同, 以 `io::ErrorKind::InvalidData` 返回
+#[cfg(feature = "std")]
+pub fn write_file<P: AsRef<Path>>(path: P, data: &BData) -> io::Result<()> {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    // 同一进程中的多个线程可能同时写入同一个文件, 每次调用使用不同的临时文件
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let id = COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(format!(".{}.{}.tmp", std::process::id(), id));
+    let tmp = path.with_file_name(tmp_name);
+
+    let res = write_new_file(&tmp, data).and_then(|_| fs::rename(&tmp, path));
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    res
+}
+
+#[cfg(feature = "std")]
+fn write_new_file(path: &Path, data: &BData) -> io::Result<()> {
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    let mut w = io::BufWriter::new(file);
+    to_writer(data, &mut w)?;
+    let file = w.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()
+}
+
+/// 将数据编码为 bencode.
+///
+/// `BigNumber` 的内容不是合法的整数时返回 `EncodeError::InvalidBigNumber`
+pub fn stringify(data: &BData) -> Result<Vec<u8>, EncodeError> {
+    stringify_with(data, &EncodeOptions::default())
+}
+
+/// 编码时的选项
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// 按字典中保存的顺序输出键, 默认按键排序输出规范的编码.
+    ///
+    /// 只在开启 `preserve-order` 时有区别, 此时可以原样输出解析时的键顺序
+    pub preserve_order: bool,
+}
+
+/// 与 `stringify` 相同, 但使用指定的编码选项
+pub fn stringify_with(data: &BData, opts: &EncodeOptions) -> Result<Vec<u8>, EncodeError> {
+    // `write_into` 按编码后的长度一次分配
+    let mut content = Vec::new();
+    write_into(data, &mut content, opts)?;
+    Ok(content)
+}
+
+/// 将数据编码后追加到 `out` 的末尾, 返回写入的字节数. 可以重复使用同一个缓冲区.
+///
+/// 出错时 `out` 保持原有的内容, 不会留下写入了一部分的数据
+pub fn stringify_into(data: &BData, out: &mut Vec<u8>) -> Result<usize, EncodeError> {
+    write_into(data, out, &EncodeOptions::default())
+}
+
+fn write_into(data: &BData, out: &mut Vec<u8>, opts: &EncodeOptions) -> Result<usize, EncodeError> {
+    let len = out.len();
+    out.reserve(encoded_len(data));
+    write_data(data, out, opts)
+        .map(|_| out.len() - len)
+        .inspect_err(|_| out.truncate(len))
+}
+
+/// 将数据编码后直接写入 `w`, 不在内存中保存完整的编码.
+///
+/// 编码错误以 `io::ErrorKind::InvalidData` 返回, 内部的错误为 `EncodeError`.
+/// 出错时已写入的部分不会被撤销
+#[cfg(feature = "std")]
+pub fn to_writer<W: Write>(data: &BData, w: &mut W) -> io::Result<()> {
+    write_data(data, &mut IoOutput(w), &EncodeOptions::default())
+}
+
+/// 编码后的字节数, 与 `stringify` 成功时的结果长度相同, 不分配内存.
+pub fn encoded_len(data: &BData) -> usize {
+    match data {
+        BData::BString(s) => string_len(s),
+        BData::Number(num) => {
+            let sign = if *num < 0 { 1 } else { 0 };
+            sign + digit_count(num.unsigned_abs()) + 2
+        }
+        BData::BigNumber(num) => num.len() + 2,
+        BData::List(vec) => vec.iter().map(encoded_len).sum::<usize>() + 2,
+        BData::Dict(dict) => {
+            dict.iter()
+                .map(|(k, v)| string_len(k) + encoded_len(v))
+                .sum::<usize>()
+                + 2
+        }
+    }
+}
+
+fn string_len(s: &[u8]) -> usize {
+    digit_count(s.len() as u64) + 1 + s.len()
+}
+
+/// 十进制表示的位数
+fn digit_count(mut n: u64) -> usize {
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+/// 编码的输出位置. 写入 `Vec` 只会出现编码错误, 写入 `io::Write` 还可能出现 IO 错误
+trait Output {
+    type Error;
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// 将编码错误转换为输出的错误类型
+    fn invalid(e: EncodeError) -> Self::Error;
+
+    /// 遇到不合法的 `BigNumber`, 默认返回 `EncodeError::InvalidBigNumber`
+    fn invalid_big_number(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        Err(Self::invalid(EncodeError::InvalidBigNumber(data.to_vec())))
+    }
+}
+
+impl Output for Vec<u8> {
+    type Error = EncodeError;
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn invalid(e: EncodeError) -> EncodeError {
+        e
+    }
+}
+
+/// 写入 `io::Write`, 编码错误以 `io::ErrorKind::InvalidData` 返回
+#[cfg(feature = "std")]
+struct IoOutput<'a, W>(&'a mut W);
+
+#[cfg(feature = "std")]
+impl<W: Write> Output for IoOutput<'_, W> {
+    type Error = io::Error;
+
+    fn put(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.0.write_all(bytes)
+    }
+
+    fn invalid(e: EncodeError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// `Display` 使用的输出, 字节按 UTF-8 有损转换, 不合法的 `BigNumber` 原样输出
+struct FmtOutput<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Output for FmtOutput<'_, '_> {
+    type Error = fmt::Error;
+
+    fn put(&mut self, bytes: &[u8]) -> fmt::Result {
+        // 字符串整体写入, 多字节字符不会被拆开
+        self.0.write_str(&String::from_utf8_lossy(bytes))
+    }
+
+    fn invalid(_: EncodeError) -> fmt::Error {
+        fmt::Error
+    }
+
+    fn invalid_big_number(&mut self, data: &[u8]) -> fmt::Result {
+        self.put(b"i")?;
+        self.put(data)?;
+        self.put(b"e")
+    }
+}
+
+fn write_data<O: Output>(data: &BData, w: &mut O, opts: &EncodeOptions) -> Result<(), O::Error> {
+    match data {
+        BData::BString(s) => write_string(s, w),
+        BData::Number(num) => {
+            w.put(b"i")?;
+            write_decimal(*num < 0, num.unsigned_abs(), w)?;
+            w.put(b"e")
+        }
+        BData::BigNumber(num) => write_big_number(num, w),
+        BData::List(vec) => write_list(vec, w, opts),
+        BData::Dict(dict) => write_dict(dict, w, opts),
+    }
+}
+
+/// 写入整数的十进制表示, 不需要分配内存
+fn write_decimal<O: Output>(negative: bool, mut n: u64, w: &mut O) -> Result<(), O::Error> {
+    let mut buf = [0; 21];
+    let mut pos = buf.len();
+    loop {
+        pos -= 1;
+        buf[pos] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    if negative {
+        pos -= 1;
+        buf[pos] = b'-';
+    }
+    w.put(&buf[pos..])
+}
+
+fn write_big_number<O: Output>(data: &[u8], w: &mut O) -> Result<(), O::Error> {
+    let digits = data.strip_prefix(b"-").unwrap_or(data);
+    let valid = !digits.is_empty()
+        && digits.iter().all(u8::is_ascii_digit)
+        && (digits[0] != b'0' || data == b"0");
+    if !valid {
+        return w.invalid_big_number(data);
+    }
+
+    w.put(b"i")?;
+    w.put(data)?;
+    w.put(b"e")
+}
+
+fn write_string<O: Output>(data: &[u8], w: &mut O) -> Result<(), O::Error> {
+    write_decimal(false, data.len() as u64, w)?;
+    w.put(b":")?;
+    w.put(data)
+}
+
+fn write_list<O: Output>(data: &[BData], w: &mut O, opts: &EncodeOptions) -> Result<(), O::Error> {
+    w.put(b"l")?;
+    for x in data {
+        write_data(x, w, opts)?;
+    }
+    w.put(b"e")
+}
+
+fn write_dict<O: Output>(
+    data: &Map<Vec<u8>, BData>,
+    w: &mut O,
+    opts: &EncodeOptions,
+) -> Result<(), O::Error> {
+    w.put(b"d")?;
+    let mut write_entry = |(k, v): (&Vec<u8>, &BData)| {
+        write_string(k, w)?;
+        write_data(v, w, opts)
+    };
+    if opts.preserve_order {
+        data.iter().try_for_each(&mut write_entry)?;
+    } else {
+        map::sorted(data).try_for_each(&mut write_entry)?;
+    }
+    w.put(b"e")
+}
+
+#[cfg(test)]
+mod test {
+    use super::map::Entry;
+    use super::{
+        BData, BKind, BPathSeg, ConflictPolicy, DictBuilder, DuplicateKeyPolicy, EncodeError,
+        IntoError, ListPolicy, Map, MergeStrategy, ParseErr, ParseErrKind, ParseOptions,
+    };
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::cmp::Ordering;
+    use std::collections::{BTreeMap, BTreeSet, HashSet};
+    use std::convert::{TryFrom, TryInto};
+    use std::env;
+    use std::error::Error;
+    use std::fs;
+    use std::io::{self, Cursor, Read};
+    use std::path::PathBuf;
+    use std::thread;
+
+    fn parse_bstring(s: &str) -> Result<String, &str> {
+        let v = super::parse_str(s);
+        if let Ok(Ok(data)) = v.map(BData::into_bytes) {
+            Ok(String::from_utf8(data).unwrap())
+        } else {
+            Err("err")
+        }
+    }
+
+    #[test]
+    fn parse_bstring_test() {
+        assert_eq!(parse_bstring("3:abc"), Ok("abc".to_string()));
+        assert_eq!(parse_bstring("3:ab"), Err("err"));
+        assert_eq!(parse_bstring("3:abcd"), Err("err"));
+        assert_eq!(parse_bstring("0:"), Ok("".to_string()));
+        assert_eq!(parse_bstring("-1:"), Err("err"));
+    }
+
+    fn parse_num(s: &str) -> Result<i64, &str> {
+        let v = super::parse_str(s);
+        if let Ok(BData::Number(data)) = v {
+            Ok(data)
+        } else {
+            Err("err")
+        }
+    }
+
+    #[test]
+    fn parse_num_test() {
+        assert_eq!(parse_num("i32e"), Ok(32));
+        assert_eq!(parse_num("i-32e"), Ok(-32));
+        assert_eq!(parse_num("i0e"), Ok(0));
+        assert_eq!(parse_num("i3.2e"), Err("err"));
+        assert_eq!(parse_num("i+32e"), Err("err"));
+        assert_eq!(parse_num("i--32e"), Err("err"));
+        assert_eq!(parse_num(&format!("i{}e", i64::MAX)), Ok(i64::MAX));
+        assert_eq!(
+            parse_num(&format!("i{}0e", i64::MAX).to_string()),
+            Err("err")
+        );
+    }
+
+    #[test]
+    fn syntax_error_offset_test() {
+        let v = super::parse(b"l3:abcXe");
+        assert!(matches!(
+            v,
+            Err(ParseErr::UnexpectedByte {
+                found: b'X',
+                expected: "value or 'e'",
+                offset: 6
+            })
+        ));
+        let v = super::parse(b"d1:ai12x3ee");
+        assert!(matches!(
+            v,
+            Err(ParseErr::UnexpectedByte {
+                found: b'x',
+                expected: "digit or 'e'",
+                offset: 7
+            })
+        ));
+        let v = super::parse(b"d1:al1:b2x:cee");
+        assert!(matches!(
+            v,
+            Err(ParseErr::UnexpectedByte {
+                found: b'x',
+                expected: "digit or ':'",
+                offset: 9
+            })
+        ));
+        let v = super::parse(b"di1ei2ee");
+        assert!(matches!(v, Err(ParseErr::NonStringDictKey { offset: 1 })));
+        let v = super::parse(b"dl1:aei1ee");
+        assert!(matches!(v, Err(ParseErr::NonStringDictKey { offset: 1 })));
+        let v = super::parse(b"d1:ae");
+        assert!(matches!(
+            v,
+            Err(ParseErr::UnexpectedByte {
+                found: b'e',
+                expected: "value",
+                offset: 4
+            })
+        ));
+        let v = super::parse(b"x");
+        assert!(matches!(
+            v,
+            Err(ParseErr::UnexpectedByte {
+                found: b'x',
+                expected: "value",
+                offset: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn granular_error_test() {
+        let v = super::parse(b"li1ei-0ee");
+        assert!(
+            matches!(v, Err(ParseErr::InvalidInteger { ref literal, offset: 4 }) if literal == "-0")
+        );
+        let v = super::parse(b"ie");
+        assert!(
+            matches!(v, Err(ParseErr::InvalidInteger { ref literal, offset: 0 }) if literal.is_empty())
+        );
+        let v = super::parse(b"i1-2e");
+        assert!(
+            matches!(v, Err(ParseErr::InvalidInteger { ref literal, offset: 0 }) if literal == "1-2")
+        );
+        let v = super::parse(b"d1:ai99999999999999999999ee");
+        assert!(matches!(
+            v,
+            Err(ParseErr::IntegerOverflow { ref literal, offset: 4 })
+                if literal == "99999999999999999999"
+        ));
+
+        let eof: [(&[u8], &str, usize); 5] = [
+            (b"", "value", 0),
+            (b"l1:a", "value or 'e'", 4),
+            (b"d", "key or 'e'", 1),
+            (b"d1:a", "value", 4),
+            (b"li12", "integer", 4),
+        ];
+        for (src, exp, off) in eof.iter() {
+            match super::parse(src) {
+                Err(ParseErr::UnexpectedEof { expected, offset }) => {
+                    assert_eq!((expected, offset), (*exp, *off), "{:?}", src);
+                }
+                v => panic!("expected UnexpectedEof, got {:?}", v),
+            }
+        }
+    }
+
+    #[test]
+    fn error_offset_test() {
+        let cases: [(&[u8], usize); 6] = [
+            (b"l3:abX", 6),
+            (b"d2:k1", 5),
+            (b"d1:al5:abc", 10),
+            (b"ld1:ai1", 7),
+            (b"li1ei2x", 6),
+            (b"d1:ade1:ai1ee", 6),
+        ];
+        for (src, offset) in cases.iter() {
+            let e = super::parse(src).unwrap_err();
+            assert_eq!(e.offset(), Some(*offset), "{:?}", e);
+        }
+    }
+
+    #[test]
+    fn error_trait_test() {
+        fn parse_boxed(src: &[u8]) -> Result<BData, Box<dyn Error + Send + Sync>> {
+            Ok(super::parse(src)?)
+        }
+        let e = parse_boxed(b"l3:abX").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "unexpected end of input at byte 6, expected value or 'e'"
+        );
+        assert!(parse_boxed(b"i1e").is_ok());
+
+        let e = super::parse(b"i99999999999999999999e").unwrap_err();
+        assert_eq!(e.kind(), ParseErrKind::Conversion);
+        let e = ParseErr::ParseFailure("x".parse::<i64>().unwrap_err().into());
+        assert!(e.source().is_some());
+        let e = super::parse(b"d1:a0:1:a0:e").unwrap_err();
+        assert_eq!(e.to_string(), "duplicate dictionary key \"a\" at byte 6");
+        assert!(e.source().is_none());
+    }
+
+    #[test]
+    fn error_box_test() {
+        fn run(src: &[u8]) -> Result<(), Box<dyn Error>> {
+            super::parse(src)?;
+            Ok(())
+        }
+        let e = run(b"lXe").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "unexpected byte 'X' at byte 1, expected value or 'e'"
+        );
+        let e = e.downcast::<ParseErr>().unwrap();
+        assert_eq!(e.offset(), Some(1));
+        assert!(run(b"le").is_ok());
+
+        let e = ParseErr::IoError(io::Error::other("closed"));
+        assert_eq!(e.source().unwrap().to_string(), "closed");
+    }
+
+    #[test]
+    fn error_kind_test() {
+        let cases: [(&[u8], ParseErrKind); 5] = [
+            (b"lXe", ParseErrKind::Syntax),
+            (b"l1:a", ParseErrKind::UnexpectedEof),
+            (b"i1ei2e", ParseErrKind::TrailingData),
+            (b"d1:ai1e1:ai2ee", ParseErrKind::DuplicateKey),
+            (b"i-0e", ParseErrKind::Syntax),
+        ];
+        for (src, kind) in cases.iter() {
+            assert_eq!(super::parse(src).unwrap_err().kind(), *kind);
+        }
+        let e = super::parse_with_limits(b"llee", 1).unwrap_err();
+        assert_eq!(e.kind(), ParseErrKind::LimitExceeded);
+        let e = super::parse_reader(FailingReader).unwrap_err();
+        assert_eq!(e.kind(), ParseErrKind::Io);
+    }
+
+    #[test]
+    fn depth_limit_test() {
+        let mut src = vec![b'l'; 10000];
+        src.extend(vec![b'e'; 10000]);
+        let v = super::parse(&src);
+        assert!(matches!(
+            v,
+            Err(ParseErr::DepthLimitExceeded {
+                offset: super::DEFAULT_MAX_DEPTH
+            })
+        ));
+        let v = super::parse_reader(Cursor::new(src.clone()));
+        assert!(matches!(v, Err(ParseErr::DepthLimitExceeded { .. })));
+
+        assert!(super::parse_with_limits(&src, 10000).is_ok());
+        let v = super::parse_with_limits(b"ld1:alee", 2);
+        assert!(matches!(v, Err(ParseErr::DepthLimitExceeded { offset: 5 })));
+    }
+
+    #[test]
+    fn parse_options_depth_test() {
+        let mut src = vec![b'l'; 1_000_000];
+        src.extend(vec![b'e'; 1_000_000]);
+        let v = super::parse_with_options(&src, &ParseOptions::default());
+        assert!(matches!(
+            v,
+            Err(ParseErr::DepthLimitExceeded { offset: 128 })
+        ));
+
+        let opts = ParseOptions {
+            max_depth: 3,
+            ..Default::default()
+        };
+        assert!(super::parse_with_options(b"ld1:ald1:bleeee", &opts).is_err());
+        assert!(super::parse_with_options(b"ld1:aleei1ee", &opts).is_ok());
+        assert!(super::parse_with_options(b"dl1:ael1:bee", &opts).is_err());
+    }
+
+    #[test]
+    fn parse_options_combination_test() {
+        // 宽松: 忽略多余字节, 重复的键保留最后一个
+        let lenient = ParseOptions {
+            allow_trailing_data: true,
+            duplicate_keys: DuplicateKeyPolicy::KeepLast,
+            ..Default::default()
+        };
+        let src = b"d1:bi01e1:ai1e1:ai2eeXYZ";
+        let data = super::parse_with_options(src, &lenient).unwrap();
+        assert_eq!(data, bdata!({"a" => 2, "b" => 1}));
+        assert!(matches!(
+            super::parse(src),
+            Err(ParseErr::DuplicateKey { offset: 14, .. })
+        ));
+        let (_, spans) = super::parse_with_spans_options(src, &lenient).unwrap();
+        assert_eq!(spans.span, 0..21);
+
+        // 严格但限制大小, 多余字节仍然是错误
+        let strict = ParseOptions {
+            max_total_bytes: 64,
+            ..ParseOptions::strict()
+        };
+        assert!(matches!(
+            super::parse_with_options(src, &strict),
+            Err(ParseErr::InvalidInteger { offset: 4, .. })
+        ));
+        assert!(matches!(
+            super::parse_with_options(b"d1:ai1ee0:", &strict),
+            Err(ParseErr::TrailingData { offset: 8 })
+        ));
+        let strict_trailing = ParseOptions {
+            allow_trailing_data: true,
+            ..strict.clone()
+        };
+        assert!(super::parse_with_options(b"d1:ai1ee0:", &strict_trailing).is_ok());
+        let long = format!("{}:{}", 70, "x".repeat(70));
+        assert!(matches!(
+            super::parse_with_options(long.as_bytes(), &strict_trailing),
+            Err(ParseErr::SizeLimitExceeded { limit: 64, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_options_limits_test() {
+        let opts = ParseOptions {
+            max_string_len: 4,
+            ..Default::default()
+        };
+        assert!(super::parse_with_options(b"l4:abcde", &opts).is_ok());
+        let v = super::parse_with_options(b"l4:abcd999999999:e", &opts);
+        assert!(matches!(
+            v,
+            Err(ParseErr::StringTooLong {
+                offset: 7,
+                declared: 999999999,
+                limit: 4
+            })
+        ));
+
+        let opts = ParseOptions {
+            max_items: 2,
+            ..Default::default()
+        };
+        assert!(super::parse_with_options(b"d1:ai1e1:bli1ei2eee", &opts).is_ok());
+        let v = super::parse_with_options(b"li1ei2ei3ee", &opts);
+        assert!(matches!(
+            v,
+            Err(ParseErr::TooManyItems {
+                offset: 7,
+                limit: 2
+            })
+        ));
+        let v = super::parse_with_options(b"d1:ai1e1:bi2e1:ci3ee", &opts);
+        assert!(matches!(v, Err(ParseErr::TooManyItems { offset: 13, .. })));
+
+        let opts = ParseOptions {
+            max_total_bytes: 8,
+            ..Default::default()
+        };
+        assert!(super::parse_with_options(b"l1:ai1ee", &opts).is_ok());
+        let v = super::parse_with_options(b"l1:ai10ee", &opts);
+        assert!(matches!(
+            v,
+            Err(ParseErr::SizeLimitExceeded {
+                offset: 8,
+                limit: 8
+            })
+        ));
+        let v = super::parse_with_options(b"999999:", &opts);
+        assert!(matches!(
+            v,
+            Err(ParseErr::SizeLimitExceeded {
+                offset: 0,
+                limit: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn declared_length_exceeds_input_test() {
+        let v = super::parse(b"10:ab");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 5, .. })));
+        let v = super::parse(b"l999999999999:abe");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 17, .. })));
+    }
+
+    #[test]
+    fn declared_length_boundary_test() {
+        assert_eq!(
+            super::parse(b"3:abc").unwrap(),
+            BData::BString(b"abc".to_vec())
+        );
+        assert_eq!(
+            super::parse(b"l3:abce").unwrap(),
+            BData::List(vec![BData::BString(b"abc".to_vec())])
+        );
+        let v = super::parse(b"4:abc");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 5, .. })));
+        let v = super::parse(b"l4:abce");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 7, .. })));
+        let v = super::parse(b"999999999:x");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 11, .. })));
+        let src = format!("{}:x", usize::MAX);
+        let v = super::parse(&src.into_bytes());
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { .. })));
+    }
+
+    #[test]
+    fn parse_large_bstring_test() {
+        let body: Vec<u8> = (0..1 << 20).map(|i: u32| (i % 251) as u8).collect();
+        let mut src = format!("d6:pieces{}:", body.len()).into_bytes();
+        src.extend_from_slice(&body);
+        src.push(b'e');
+        let data = super::parse(&src).unwrap();
+        assert_eq!(data, bdata!({"pieces" => body}));
+        assert_eq!(super::stringify(&data).unwrap(), src);
+
+        // 缺少最后一个字节
+        let v = super::parse(&src[..src.len() - 2]);
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { .. })));
+    }
+
+    #[test]
+    fn drop_deep_small_stack_test() {
+        let handle = thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let mut data = bdata!({"leaf" => [1, "a"]});
+                for i in 0..100_000 {
+                    data = if i % 2 == 0 {
+                        BData::List(vec![bdata!(1), data, bdata!([])])
+                    } else {
+                        bdata!({"a" => data, "b" => {}})
+                    };
+                }
+                drop(data);
+            })
+            .unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn deep_nesting_small_stack_test() {
+        let depth = 50_000;
+        let handle = thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || {
+                let mut src = vec![b'l'; depth];
+                src.extend(vec![b'e'; depth]);
+                let opts = ParseOptions {
+                    max_depth: depth,
+                    ..Default::default()
+                };
+                let data = super::parse_with_options(&src, &opts).expect("parse failed");
+                // 析构同样不能按层数递归
+                data.max_depth()
+            })
+            .unwrap();
+        assert_eq!(handle.join().unwrap(), depth);
+    }
+
+    #[test]
+    fn parse_num_invalid_body_test() {
+        for s in ["i-0e", "ie", "i-e", "i+e"] {
+            let v = super::parse_str(s);
+            assert_eq!(v.unwrap_err().kind(), ParseErrKind::Syntax, "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_big_number_test() {
+        let opts = ParseOptions {
+            big_numbers: true,
+            ..Default::default()
+        };
+        let hundred = format!("1{}", "0".repeat(99));
+        let sources = [
+            u64::MAX.to_string(),
+            hundred.clone(),
+            format!("-{}", hundred),
+            "-9223372036854775809".to_string(),
+        ];
+        for digits in sources.iter() {
+            let src = format!("i{}e", digits);
+            let v = super::parse_str(&src);
+            assert!(
+                matches!(v, Err(ParseErr::IntegerOverflow { .. })),
+                "{}",
+                src
+            );
+
+            let data = super::parse_with_options(src.as_bytes(), &opts).unwrap();
+            assert_eq!(data, BData::BigNumber(digits.as_bytes().to_vec()));
+            assert_eq!(super::stringify(&data).unwrap(), src.into_bytes());
+        }
+
+        let src = format!("li1ei{}ee", u64::MAX);
+        let data = super::parse_with_options(src.as_bytes(), &opts).unwrap();
+        assert_eq!(super::stringify(&data).unwrap(), src.into_bytes());
+        let v = super::parse_with_options(b"i9223372036854775807e", &opts);
+        assert_eq!(v.unwrap(), BData::Number(i64::MAX));
+        let v = super::parse_with_options(b"i1-2e", &opts);
+        assert!(matches!(v, Err(ParseErr::InvalidInteger { .. })));
+    }
+
+    #[test]
+    fn parse_strict_integers_test() {
+        assert_eq!(super::parse(b"i03e").unwrap(), BData::Number(3));
+        assert_eq!(
+            super::parse(b"03:abc").unwrap(),
+            BData::BString(b"abc".to_vec())
+        );
+        for s in [
+            "i03e", "i-03e", "i00e", "03:abc", "l00:e", "i-0e", "ie", "i+5e",
+        ] {
+            let v = super::parse_strict(s.as_bytes());
+            assert_eq!(v.unwrap_err().kind(), ParseErrKind::Syntax, "{}", s);
+        }
+        let v = super::parse_strict(b"li1ei03ee");
+        assert!(
+            matches!(v, Err(ParseErr::InvalidInteger { ref literal, offset: 4 }) if literal == "03")
+        );
+        assert_eq!(super::parse_strict(b"i0e").unwrap(), BData::Number(0));
+        assert_eq!(super::parse_strict(b"i-10e").unwrap(), BData::Number(-10));
+    }
+
+    #[test]
+    fn parse_strict_round_trip_test() {
+        let sources: [&[u8]; 7] = [
+            b"i0e",
+            b"i-9223372036854775808e",
+            b"0:",
+            b"10:0123456789",
+            b"le",
+            b"d1:ai0e1:bli-1e0:e2:\xffxdee",
+            b"d4:infod6:lengthi1024e4:name5:a.txtee",
+        ];
+        for src in sources.iter() {
+            let data = super::parse_strict(src).expect("parse_strict failed");
+            assert_eq!(super::stringify(&data).unwrap(), src.to_vec());
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        let sources: [&[u8]; 7] = [
+            b"i0e",
+            b"i-9223372036854775808e",
+            b"0:",
+            b"le",
+            b"d1:ai0e1:bli-1e0:e2:\xffxdee",
+            b"ld1:bi1eed1:ai2eee",
+            b"d1:ad1:bi1e1:cdee1:bd1:ai1eee",
+        ];
+        for src in sources.iter() {
+            assert!(super::validate(src).is_ok(), "{:?}", src);
+        }
+
+        // 错误与 parse_strict 相同
+        let sources: [&[u8]; 12] = [
+            b"",
+            b"l",
+            b"lei1e",
+            b"lee",
+            b"i1x",
+            b"i03e",
+            b"i-0e",
+            b"5:abc",
+            b"di1ei2ee",
+            b"d1:ai1e1:ai2ee",
+            b"d1:bi1e1:ai2ee",
+            b"d1:ad1:bi1e1:ai1eee",
+        ];
+        for src in sources.iter() {
+            let e = super::validate(src).unwrap_err();
+            let expected = super::parse_strict(src).unwrap_err();
+            assert_eq!(format!("{:?}", e), format!("{:?}", expected));
+        }
+        let e = super::validate(b"d1:ai1e1:ai2ee").unwrap_err();
+        assert!(matches!(e, ParseErr::DuplicateKey { offset: 7, .. }));
+        let e = super::validate(b"d1:ad1:bi1e1:ai1eee").unwrap_err();
+        assert!(matches!(e, ParseErr::UnsortedKeys { offset: 11 }));
+    }
+
+    #[test]
+    fn is_canonical_test() {
+        let sources: [&[u8]; 6] = [
+            b"i0e",
+            b"i-1e",
+            b"i-99999999999999999999e",
+            b"10:0123456789",
+            b"d0:le1:ad1:ai0eee",
+            b"d4:infod6:lengthi1024e4:name5:a.txtee",
+        ];
+        for src in sources.iter() {
+            assert!(super::is_canonical(src), "{:?}", src);
+            let opts = ParseOptions {
+                big_numbers: true,
+                ..ParseOptions::strict()
+            };
+            let data = super::parse_with_options(src, &opts).unwrap();
+            assert_eq!(super::stringify(&data).unwrap(), *src);
+        }
+
+        let sources: [&[u8]; 12] = [
+            b"",
+            b"i01e",
+            b"i-0e",
+            b"i-01e",
+            b"i00099999999999999999999e",
+            b"03:abc",
+            b"d1:bi1e1:ai2ee",
+            b"d1:ai1e1:ai2ee",
+            b"ld1:bi1e1:ai2eee",
+            b"i1ei2e",
+            b"le\n",
+            b"l1:a",
+        ];
+        for src in sources.iter() {
+            assert!(!super::is_canonical(src), "{:?}", src);
+            let opts = ParseOptions::default();
+            assert!(matches!(super::is_canonical_with(src, &opts), Ok(false)));
+        }
+    }
+
+    #[test]
+    fn is_canonical_with_test() {
+        let mut src = vec![b'l'; 200];
+        src.extend(vec![b'e'; 200]);
+        assert!(!super::is_canonical(&src));
+        let v = super::is_canonical_with(&src, &ParseOptions::default());
+        assert!(matches!(
+            v,
+            Err(ParseErr::DepthLimitExceeded { offset: 128 })
+        ));
+
+        // 只使用 `opts` 中的限制, 规范编码的规则不变
+        let opts = ParseOptions {
+            max_depth: 200,
+            duplicate_keys: DuplicateKeyPolicy::KeepLast,
+            allow_trailing_data: true,
+            ..Default::default()
+        };
+        assert!(matches!(super::is_canonical_with(&src, &opts), Ok(true)));
+        src.push(b'x');
+        assert!(matches!(super::is_canonical_with(&src, &opts), Ok(false)));
+        let v = super::is_canonical_with(b"d1:ai1e1:ai2ee", &opts);
+        assert!(matches!(v, Ok(false)));
+        let opts = ParseOptions {
+            max_total_bytes: 4,
+            ..Default::default()
+        };
+        let v = super::is_canonical_with(b"5:abcde", &opts);
+        assert!(matches!(v, Err(ParseErr::SizeLimitExceeded { .. })));
+    }
+
+    fn parse_list(s: &str) -> Result<Vec<BData>, &str> {
+        let v = super::parse_str(s);
+        if let Ok(Ok(rc)) = v.map(BData::into_list) {
+            Ok(rc)
+        } else {
+            Err("err")
+        }
+    }
+
+    fn parse_list_check(s: &'static str, check: &[BData]) {
+        let v = parse_list(s);
+        match v {
+            Ok(rc) => {
+                let mut ch = check.iter();
+                for e in rc.iter() {
+                    if let Some(data) = ch.next() {
+                        assert_eq!(data, e);
+                    } else {
+                        panic!("more elements than expected");
+                    }
+                }
+            }
+            Err(_) => panic!("parse list failed"),
+        };
+    }
+
+    #[test]
+    fn parse_list_test() {
+        parse_list_check("le", &[]);
+        parse_list_check("l3:abce", &[BData::BString("abc".as_bytes().to_vec())]);
+        parse_list_check(
+            "l3:abc4:abcde",
+            &[
+                BData::BString("abc".as_bytes().to_vec()),
+                BData::BString("abcd".as_bytes().to_vec()),
+            ],
+        );
+        parse_list_check(
+            "l3:abci32el2:abee",
+            &[
+                BData::BString("abc".as_bytes().to_vec()),
+                BData::Number(32),
+                BData::List(vec![BData::BString("ab".as_bytes().to_vec())]),
+            ],
+        );
+    }
+
+    fn parse_dict(s: &str) -> Result<Map<Vec<u8>, BData>, &str> {
+        let v = super::parse_str(s);
+        if let Ok(Ok(map)) = v.map(BData::into_dict) {
+            Ok(map)
+        } else {
+            Err("err")
+        }
+    }
+
+    fn parse_dict_check(s: &str, map: &Map<Vec<u8>, BData>) {
+        let data = parse_dict(s);
+
+        let m = data.expect("parse dict failed");
+
+        assert_eq!(m.len(), map.len());
+        m.iter().for_each(|x| {
+            assert!(map.contains_key(x.0));
+            assert_eq!(x.1, map.get(x.0).unwrap());
+        });
+    }
+
+    #[test]
+    fn parse_dict_test() {
+        parse_dict_check("de", &Map::new());
+        let source = "d2:k13:abce";
+        let mut m = Map::new();
+        m.insert(b"k1".to_vec(), BData::BString("abc".as_bytes().to_vec()));
+        parse_dict_check(source, &m);
+
+        let mut m = Map::new();
+        let source = "d2:k13:abc2:k2l3:defi-23eee";
+        m.insert(b"k1".to_vec(), BData::BString("abc".as_bytes().to_vec()));
+        let k2_list = vec![
+            BData::BString("def".as_bytes().to_vec()),
+            BData::Number(-23),
+        ];
+        m.insert(b"k2".to_vec(), BData::List(k2_list));
+        parse_dict_check(source, &m);
+    }
+
+    #[test]
+    fn parse_dict_binary_key_test() {
+        let src = b"d2:k23:def2:\xff\xfe3:abce".to_vec();
+        let mut m = Map::new();
+        m.insert(b"\xff\xfe".to_vec(), BData::BString(b"abc".to_vec()));
+        m.insert(b"k2".to_vec(), BData::BString(b"def".to_vec()));
+        let data = super::parse(&src).expect("parse dict failed");
+        assert_eq!(data, BData::Dict(m));
+        assert_eq!(super::stringify(&data).unwrap(), src);
+    }
+
+    #[test]
+    fn parse_dict_binary_key_followed_by_entries_test() {
+        // 二进制键之后的值以及其余的键值对都必须正常读取
+        let src = b"d2:\xff\xfed1:\x00i1ee1:ali2ee1:b0:e";
+        let data = super::parse(src).expect("parse dict failed");
+        let dict = data.as_dict().unwrap();
+        assert_eq!(dict.len(), 3);
+        let inner = dict[&b"\xff\xfe"[..]].as_dict().unwrap();
+        assert_eq!(inner[&b"\x00"[..]], BData::Number(1));
+        assert_eq!(dict[&b"a"[..]], BData::List(vec![BData::Number(2)]));
+        assert_eq!(dict[&b"b"[..]], BData::BString(Vec::new()));
+
+        let encoded = super::stringify(&data).unwrap();
+        assert_eq!(encoded, b"d1:ali2ee1:b0:2:\xff\xfed1:\x00i1eee".to_vec());
+        assert_eq!(super::parse(&encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn parse_dict_invalid_utf8_key_test() {
+        let data = super::parse(b"d1:\xff3:abc2:k23:defe").expect("parse dict failed");
+        let mut m = Map::new();
+        m.insert(b"\xff".to_vec(), BData::BString(b"abc".to_vec()));
+        m.insert(b"k2".to_vec(), BData::BString(b"def".to_vec()));
+        assert_eq!(data, BData::Dict(m));
+    }
+
+    #[test]
+    fn accessor_test() {
+        let data = super::parse_str("d4:infod6:lengthi1024e4:name5:a.txte4:listl1:xee").unwrap();
+        let info = data.as_dict().unwrap()[&b"info"[..]].as_dict().unwrap();
+        assert_eq!(info[&b"name"[..]].as_str(), Some("a.txt"));
+        assert_eq!(info[&b"name"[..]].as_bytes(), Some(&b"a.txt"[..]));
+        assert_eq!(info[&b"length"[..]].as_number(), Some(1024));
+        let list = data.as_dict().unwrap()[&b"list"[..]].as_list().unwrap();
+        assert_eq!(list, &[BData::BString(b"x".to_vec())]);
+
+        assert_eq!(BData::Number(1).as_bytes(), None);
+        assert_eq!(BData::Number(1).as_str(), None);
+        assert_eq!(BData::BString(b"1".to_vec()).as_number(), None);
+        assert_eq!(BData::BString(b"\xff".to_vec()).as_str(), None);
+        assert!(BData::List(vec![]).as_dict().is_none());
+        assert!(BData::Dict(Map::new()).as_list().is_none());
+    }
+
+    #[test]
+    fn accessor_mut_test() {
+        let mut data =
+            super::parse_str("d4:infod6:lengthi1024e4:name5:a.txte4:listl1:xee").unwrap();
+        let dict = data.as_dict_mut().unwrap();
+        dict.get_mut(&b"list"[..])
+            .and_then(BData::as_list_mut)
+            .unwrap()
+            .push(bdata!(2));
+        let info = dict
+            .get_mut(&b"info"[..])
+            .and_then(BData::as_dict_mut)
+            .unwrap();
+        *info
+            .get_mut(&b"length"[..])
+            .and_then(BData::as_number_mut)
+            .unwrap() += 1;
+        info.get_mut(&b"name"[..])
+            .and_then(BData::as_bytes_mut)
+            .unwrap()
+            .push(b'~');
+        assert_eq!(
+            data,
+            bdata!({"info" => {"length" => 1025, "name" => "a.txt~"}, "list" => ["x", 2]})
+        );
+
+        let mut data = bdata!(1);
+        assert!(data.as_bytes_mut().is_none());
+        assert!(data.as_list_mut().is_none());
+        assert!(data.as_dict_mut().is_none());
+        assert!(bdata!("1").as_number_mut().is_none());
+    }
+
+    #[test]
+    fn iter_test() {
+        let data = bdata!({"files" => [
+            {"length" => 3, "path" => ["a"]},
+            {"length" => 5, "path" => ["b", "c"]},
+        ], "name" => "dir"});
+        let files = data.as_dict().unwrap()[&b"files"[..]].iter_list().unwrap();
+        let total: i64 = files
+            .map(|f| f.as_dict().unwrap()[&b"length"[..]].as_number().unwrap())
+            .sum();
+        assert_eq!(total, 8);
+
+        let keys: Vec<&[u8]> = data.iter_dict().unwrap().map(|(k, _)| k).collect();
+        assert_eq!(keys, [&b"files"[..], &b"name"[..]]);
+        let (_, name) = data.iter_dict().unwrap().last().unwrap();
+        assert_eq!(name.as_str(), Some("dir"));
+        assert_eq!(bdata!([]).iter_list().unwrap().count(), 0);
+
+        assert!(data.iter_list().is_none());
+        assert!(bdata!([]).iter_dict().is_none());
+        assert!(bdata!("x").iter_list().is_none());
+    }
+
+    #[test]
+    fn get_path_test() {
+        let mut data = bdata!({
+            "info" => {
+                "files" => [{"length" => 3, "path" => ["a"]}, {"length" => 5}],
+                "name" => "dir",
+            },
+            "a.b/c" => {"\\" => 1},
+            "" => {"" => 2},
+        });
+        assert_eq!(data.get_path("info.files.1.length"), Some(&bdata!(5)));
+        assert_eq!(data.get_path("info/files/0/path/0"), Some(&bdata!("a")));
+        assert_eq!(data.get_path("info.files/0.length"), Some(&bdata!(3)));
+        assert_eq!(data.get_path(""), Some(&data));
+        // 转义分隔符与反斜杠
+        assert_eq!(data.get_path("a\\.b\\/c.\\\\"), Some(&bdata!(1)));
+        // 空的键
+        assert_eq!(data.get_path("."), Some(&bdata!(2)));
+
+        let missing = [
+            "info.files.2",
+            "info.files.-1",
+            "info.files.+1",
+            "info.files.x",
+            "info.size",
+            "info.name.0",
+            "info.files.1.length.0",
+            "a.b/c",
+        ];
+        for path in missing.iter() {
+            assert_eq!(data.get_path(path), None, "{}", path);
+        }
+
+        *data.get_path_mut("info.files.0.length").unwrap() = bdata!(4);
+        assert_eq!(data.get_path("info.files.0.length"), Some(&bdata!(4)));
+        assert!(data.get_path_mut("info.name.x").is_none());
+    }
+
+    #[test]
+    fn pointer_test() {
+        let mut data = bdata!({
+            "info" => {
+                "files" => [{"length" => 3, "path" => ["a"]}, {"length" => 5}],
+                "name" => "dir",
+            },
+            &b"\xff"[..] => [[1]],
+            "0" => "zero",
+        });
+        let length = [
+            BPathSeg::Key(b"info"),
+            BPathSeg::Key(b"files"),
+            BPathSeg::Index(1),
+            BPathSeg::Key(b"length"),
+        ];
+        assert_eq!(data.pointer(&length), Some(&bdata!(5)));
+        assert_eq!(data.pointer(&length), data.get_path("info/files/1/length"));
+        assert_eq!(data.pointer(&[]), Some(&data));
+        let path: [BPathSeg; 3] = [(&b"\xff"[..]).into(), 0.into(), 0.into()];
+        assert_eq!(data.pointer(&path), Some(&bdata!(1)));
+
+        let missing: [&[BPathSeg]; 5] = [
+            &["info".into(), "size".into()],
+            &["info".into(), "files".into(), 2.into()],
+            // 下标不能作为字典的键, 键也不能作为列表的下标
+            &[0.into()],
+            &["info".into(), "files".into(), "0".into()],
+            &["info".into(), "name".into(), 0.into()],
+        ];
+        for path in missing.iter() {
+            assert_eq!(data.pointer(path), None, "{:?}", path);
+        }
+        assert_eq!(data.pointer(&["0".into()]), Some(&bdata!("zero")));
+
+        *data.pointer_mut(&length).unwrap() = bdata!(6);
+        assert_eq!(data.get_path("info.files.1.length"), Some(&bdata!(6)));
+        assert!(data.pointer_mut(missing[0]).is_none());
+    }
+
+    #[test]
+    fn index_test() {
+        let mut data = bdata!({"info" => {"name" => "a", "files" => [{"length" => 3}]}});
+        assert_eq!(data["info"]["name"], bdata!("a"));
+        assert_eq!(data["info"]["files"][0]["length"], bdata!(3));
+
+        data["info"]["name"] = bdata!("b");
+        data["info"]["files"][0]["length"] = bdata!(4);
+        data["announce"] = bdata!("http://t");
+        data["x"]["y"]["z"] = bdata!(1);
+        assert_eq!(
+            data,
+            bdata!({
+                "announce" => "http://t",
+                "info" => {"files" => [{"length" => 4}], "name" => "b"},
+                "x" => {"y" => {"z" => 1}},
+            })
+        );
+    }
+
+    /// 取出 panic 时的信息
+    fn panic_message<F: FnOnce() + std::panic::UnwindSafe>(f: F) -> String {
+        let e = std::panic::catch_unwind(f).unwrap_err();
+        match e.downcast::<String>() {
+            Ok(msg) => *msg,
+            Err(e) => e.downcast::<&str>().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn index_panic_test() {
+        let data = bdata!({"list" => [1, 2], "n" => 1});
+        assert_eq!(
+            panic_message(|| {
+                let _ = &data["name"];
+            }),
+            "no key \"name\" in dict"
+        );
+        assert_eq!(
+            panic_message(|| {
+                let _ = &data["n"]["x"];
+            }),
+            "cannot index number with key \"x\""
+        );
+        assert_eq!(
+            panic_message(|| {
+                let _ = &data["list"][2];
+            }),
+            "index 2 out of range for list of length 2"
+        );
+        assert_eq!(
+            panic_message(|| {
+                let _ = &data[0];
+            }),
+            "cannot index dict with 0"
+        );
+        assert_eq!(
+            panic_message(|| bdata!([])[1] = bdata!(1)),
+            "index 1 out of range for list of length 0"
+        );
+        assert_eq!(
+            panic_message(|| bdata!("s")["k"] = bdata!(1)),
+            "cannot index bytes with key \"k\""
+        );
+    }
+
+    #[test]
+    fn iter_mut_test() {
+        let mut info = bdata!({"files" => [
+            {"length" => 3, "path" => ["a"]},
+            {"length" => 5, "path" => ["b", "c"]},
+        ], "name" => "dir"});
+
+        let mut paths = Vec::new();
+        for file in info
+            .get("files")
+            .and_then(BData::iter_list)
+            .into_iter()
+            .flatten()
+        {
+            let path = file
+                .get("path")
+                .and_then(BData::iter_list)
+                .into_iter()
+                .flatten();
+            paths.push(path.filter_map(BData::as_str).collect::<Vec<_>>().join("/"));
+        }
+        assert_eq!(paths, ["a", "b/c"]);
+
+        let files = info.get_mut("files").unwrap();
+        for file in files.iter_list_mut().unwrap() {
+            for (k, v) in file.iter_dict_mut().unwrap() {
+                if k == b"length" {
+                    *v = bdata!(v.as_number().unwrap() * 2);
+                }
+            }
+        }
+        assert_eq!(info.get_path("files.0.length"), Some(&bdata!(6)));
+        assert_eq!(info.get_path("files.1.length"), Some(&bdata!(10)));
+
+        // 类型不符时返回 `None`, 而不是空的迭代器
+        assert!(info.iter_list_mut().is_none());
+        assert!(info.get_mut("files").unwrap().iter_dict_mut().is_none());
+        assert!(bdata!(1).iter_list_mut().is_none());
+        // 取得所有权后遍历
+        let files = info.get("files").cloned().unwrap().into_list().unwrap();
+        let lengths: Vec<BData> = files
+            .into_iter()
+            .filter_map(|f| f.get("length").cloned())
+            .collect();
+        assert_eq!(lengths, [bdata!(6), bdata!(10)]);
+    }
+
+    #[test]
+    fn merge_test() {
+        let mut base = bdata!({
+            "announce" => "http://default",
+            "info" => {"name" => "a", "private" => 0, "files" => [1, 2]},
+            "keep" => [],
+        });
+        let overlay = bdata!({
+            "announce" => "http://t",
+            "info" => {"private" => 1, "files" => [3], "source" => "x"},
+            "new" => {"a" => {}},
+        });
+        super::merge(&mut base, overlay);
+        assert_eq!(
+            base,
+            bdata!({
+                "announce" => "http://t",
+                "info" => {"files" => [3], "name" => "a", "private" => 1, "source" => "x"},
+                "keep" => [],
+                "new" => {"a" => {}},
+            })
+        );
+
+        // 类型不同时整体替换
+        let mut base = bdata!({"a" => {"b" => 1}, "c" => [1]});
+        super::merge(&mut base, bdata!({"a" => "x", "c" => {"d" => 2}}));
+        assert_eq!(base, bdata!({"a" => "x", "c" => {"d" => 2}}));
+        super::merge(&mut base, bdata!([1]));
+        assert_eq!(base, bdata!([1]));
+        let mut base = bdata!(1);
+        super::merge(&mut base, bdata!({}));
+        assert_eq!(base, bdata!({}));
+        super::merge(&mut base, bdata!({}));
+        assert_eq!(base, bdata!({}));
+    }
+
+    #[test]
+    fn merge_strategy_test() {
+        let base = || {
+            bdata!({
+                "a" => {"b" => {"c" => 1, "d" => [1]}, "e" => [1]},
+                "f" => [1],
+                "g" => {"h" => 1},
+            })
+        };
+        let other = || {
+            bdata!({
+                "a" => {"b" => {"c" => 2, "d" => [2], "x" => 0}, "e" => {"y" => 0}},
+                "f" => [2],
+                "g" => [2],
+            })
+        };
+        let strategy = |conflicts, lists| MergeStrategy { conflicts, lists };
+        let cases = [
+            (
+                MergeStrategy::default(),
+                bdata!({
+                    "a" => {"b" => {"c" => 2, "d" => [2], "x" => 0}, "e" => {"y" => 0}},
+                    "f" => [2],
+                    "g" => [2],
+                }),
+            ),
+            (
+                strategy(ConflictPolicy::KeepSelf, ListPolicy::Replace),
+                bdata!({
+                    "a" => {"b" => {"c" => 1, "d" => [1], "x" => 0}, "e" => [1]},
+                    "f" => [1],
+                    "g" => {"h" => 1},
+                }),
+            ),
+            (
+                strategy(ConflictPolicy::OverwriteWithOther, ListPolicy::Concat),
+                bdata!({
+                    "a" => {"b" => {"c" => 2, "d" => [1, 2], "x" => 0}, "e" => {"y" => 0}},
+                    "f" => [1, 2],
+                    "g" => [2],
+                }),
+            ),
+            (
+                strategy(ConflictPolicy::KeepSelf, ListPolicy::Concat),
+                bdata!({
+                    "a" => {"b" => {"c" => 1, "d" => [1, 2], "x" => 0}, "e" => [1]},
+                    "f" => [1, 2],
+                    "g" => {"h" => 1},
+                }),
+            ),
+        ];
+        for (strategy, expected) in cases.iter() {
+            let mut data = base();
+            data.merge(other(), *strategy);
+            assert_eq!(data, *expected, "{:?}", strategy);
+        }
+
+        // 不是字典时不做任何事
+        let mut list = bdata!([1]);
+        list.merge(
+            bdata!([2]),
+            strategy(ConflictPolicy::OverwriteWithOther, ListPolicy::Concat),
+        );
+        assert_eq!(list, bdata!([1]));
+        let mut data = base();
+        data.merge(bdata!([2]), MergeStrategy::default());
+        assert_eq!(data, bdata!([2]));
+    }
+
+    #[test]
+    fn into_no_copy_test() {
+        let mut pieces = vec![0xab; 20];
+        pieces.resize(1 << 20, 0);
+        let mut data = bdata!({"info" => {"name" => "a", "pieces" => pieces}});
+        let ptr = data["info"]["pieces"].as_bytes().unwrap().as_ptr();
+
+        // 取出的字节与原数据共用同一块内存
+        let mut info = data.remove("info").unwrap();
+        let pieces = info.remove("pieces").unwrap().into_bytes().unwrap();
+        assert_eq!(pieces.as_ptr(), ptr);
+        assert_eq!(pieces.len(), 1 << 20);
+
+        // 类型不符时原样取回
+        let list = BData::List(vec![BData::BString(pieces)]);
+        let list = list.into_dict().unwrap_err().into_bytes().unwrap_err();
+        let mut list = list.into_list().unwrap();
+        let pieces = list.pop().unwrap().into_bytes().unwrap();
+        assert_eq!(pieces.as_ptr(), ptr);
+        assert_eq!(info.into_dict().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn take_test() {
+        let mut pieces = vec![0xab; 20];
+        pieces.resize(1 << 20, 0);
+        let mut src = bdata!({
+            "announce" => "http://t",
+            "info" => {"name" => "a", "pieces" => pieces},
+            "list" => [1, 2, 3],
+        });
+        let ptr = src["info"]["pieces"].as_bytes().unwrap().as_ptr();
+
+        // 将 info 移到另一个数据中, 字符串没有被复制
+        let info = src.take_path("info").unwrap();
+        let mut dst = bdata!({"info" => {}});
+        let old = dst["info"].replace(info);
+        assert_eq!(old, bdata!({}));
+        assert_eq!(dst["info"]["pieces"].as_bytes().unwrap().as_ptr(), ptr);
+        assert!(!src.contains_key("info"));
+
+        // 列表的元素前移
+        assert_eq!(src.take_path("list/1"), Some(bdata!(2)));
+        assert_eq!(src["list"], bdata!([1, 3]));
+        let missing = ["list/2", "list/x", "announce/0", "info/name", "x"];
+        for path in missing.iter() {
+            assert_eq!(src.take_path(path), None, "{}", path);
+        }
+        assert_eq!(src, bdata!({"announce" => "http://t", "list" => [1, 3]}));
+
+        let name = dst.take_path("info.name").unwrap();
+        assert_eq!(name, bdata!("a"));
+        let pieces = dst["info"]["pieces"].take();
+        assert_eq!(pieces.as_bytes().unwrap().as_ptr(), ptr);
+        assert_eq!(dst, bdata!({"info" => {"pieces" => ""}}));
+        assert_eq!(
+            dst.take_path(""),
+            Some(bdata!({"info" => {"pieces" => ""}}))
+        );
+        assert_eq!(dst, BData::default());
+    }
+
+    #[test]
+    fn into_test() {
+        let data = bdata!({"announce" => "http://t", "list" => [1, &b"\xff"[..]]});
+        let mut dict = data.into_dict().unwrap().into_iter();
+        let (_, announce) = dict.next().unwrap();
+        assert_eq!(announce.into_string().unwrap(), "http://t");
+
+        // 类型不符时取回原来的数据再尝试其他类型
+        let (_, list) = dict.next().unwrap();
+        let list = list.into_dict().unwrap_err().into_list().unwrap();
+        let n = list[0].clone().into_bytes().unwrap_err();
+        assert_eq!(n.into_number(), Ok(1));
+
+        let e = list[1].clone().into_string().unwrap_err();
+        assert!(matches!(e, IntoError::InvalidUtf8(_)));
+        assert!(e.source().is_some());
+        assert_eq!(e.into_data().into_bytes(), Ok(b"\xff".to_vec()));
+
+        let e = bdata!([]).into_string().unwrap_err();
+        assert_eq!(e.to_string(), "value is not a string");
+        assert_eq!(e.into_data().into_list(), Ok(vec![]));
+
+        assert_eq!(bdata!("1").into_number(), Err(bdata!("1")));
+        assert_eq!(bdata!(1).into_list(), Err(bdata!(1)));
+        assert_eq!(bdata!(1).into_dict(), Err(bdata!(1)));
+    }
+
+    #[test]
+    fn get_test() {
+        let mut data = bdata!({
+            "announce" => "http://old",
+            "info" => {"name" => "a", "files" => [{"length" => 1}]},
+            &b"\xff"[..] => 0,
+        });
+        *data.get_mut("announce").unwrap() = bdata!("http://new");
+        let info = data.get_mut("info").unwrap();
+        *info.get_mut("name").unwrap() = bdata!("b");
+        let files = info.get_mut("files").unwrap();
+        *files[0].get_mut("length").unwrap() = bdata!(2);
+
+        assert_eq!(data.get("announce"), Some(&bdata!("http://new")));
+        let info = data.get("info").unwrap();
+        assert_eq!(info.get("name").and_then(BData::as_str), Some("b"));
+        assert_eq!(info.get_path("files.0.length"), Some(&bdata!(2)));
+        assert_eq!(data.get(&b"\xff"[..]), Some(&bdata!(0)));
+
+        assert!(data.contains_key("info"));
+        assert!(data.contains_key(&b"\xff"[..]));
+        assert!(!data.contains_key("size"));
+        assert!(data.get("size").is_none());
+        assert!(bdata!(["info"]).get("info").is_none());
+        assert!(!bdata!("info").contains_key("info"));
+        assert!(bdata!(1).get_mut("info").is_none());
+    }
+
+    #[test]
+    fn try_from_test() {
+        let data = bdata!({
+            "info" => {"name" => "a", "sizes" => [1, 2, 3], "files" => [["a", "b"], ["c"]]},
+        });
+        let info = data.get("info").unwrap();
+        let sizes: Vec<i64> = info.get("sizes").unwrap().try_into().unwrap();
+        assert_eq!(sizes, [1, 2, 3]);
+        let files: Vec<Vec<String>> = info.get("files").unwrap().try_into().unwrap();
+        assert_eq!(files, [vec!["a", "b"], vec!["c"]]);
+        let name = String::try_from(info.get("name").unwrap()).unwrap();
+        assert_eq!(name, "a");
+
+        let dict = BTreeMap::<String, BData>::try_from(data.clone()).unwrap();
+        let info = BTreeMap::<String, BData>::try_from(&dict["info"]).unwrap();
+        let sizes: Vec<BData> = info["sizes"].clone().try_into().unwrap();
+        assert_eq!(sizes, [bdata!(1), bdata!(2), bdata!(3)]);
+        let sizes: Vec<i64> = info["sizes"].clone().try_into().unwrap();
+        assert_eq!(sizes, [1, 2, 3]);
+        assert_eq!(Vec::<u8>::try_from(bdata!("ab")), Ok(b"ab".to_vec()));
+        assert_eq!(i64::try_from(bdata!(-1)), Ok(-1));
+    }
+
+    #[test]
+    fn try_from_err_test() {
+        use super::TryFromError;
+
+        let e = i64::try_from(bdata!([])).unwrap_err();
+        assert_eq!(
+            e,
+            TryFromError::WrongType {
+                expected: "number",
+                found: "list"
+            }
+        );
+        assert_eq!(e.to_string(), "expected number, found list");
+        assert_eq!(
+            String::try_from(&bdata!(&b"\xff"[..])),
+            Err(TryFromError::InvalidUtf8)
+        );
+        let e = BTreeMap::<String, BData>::try_from(bdata!({&b"\xff"[..] => 1})).unwrap_err();
+        assert_eq!(e, TryFromError::InvalidUtf8);
+
+        // 出错的元素在嵌套列表中的位置
+        let data = bdata!([[1, 2], [3, "x"]]);
+        let e = Vec::<Vec<i64>>::try_from(&data).unwrap_err();
+        let inner = TryFromError::WrongType {
+            expected: "number",
+            found: "bytes",
+        };
+        let expected = TryFromError::Element {
+            index: 1,
+            source: Box::new(TryFromError::Element {
+                index: 1,
+                source: Box::new(inner),
+            }),
+        };
+        assert_eq!(e, expected);
+        assert_eq!(
+            e.to_string(),
+            "list element 1: list element 1: expected number, found bytes"
+        );
+        assert!(e.source().is_some());
+        assert_eq!(Vec::<Vec<i64>>::try_from(data), Err(expected));
+        assert_eq!(
+            Vec::<i64>::try_from(bdata!({})),
+            Err(TryFromError::WrongType {
+                expected: "list",
+                found: "dict"
+            })
+        );
+    }
+
+    #[test]
+    fn edit_test() {
+        let src = b"d8:announce8:http://a13:announce-listll8:http://aee\
+4:infod6:lengthi5e4:name5:a.txte8:url-listl8:http://xee";
+        let mut data = super::parse(src).unwrap();
+
+        assert_eq!(
+            data.insert("announce", "http://b"),
+            Some(bdata!("http://a"))
+        );
+        assert_eq!(data.remove("announce-list"), Some(bdata!([["http://a"]])));
+        assert_eq!(data.remove("announce-list"), None);
+        assert_eq!(data.insert("comment", "c"), None);
+        let info = data.get_mut("info").unwrap();
+        info.insert(&b"private"[..], 1);
+        assert_eq!(info.remove("length"), Some(bdata!(5)));
+
+        let urls = data.get_mut("url-list").unwrap();
+        *urls.push("http://y").unwrap() = bdata!("http://z");
+        urls.push("http://w");
+        assert_eq!(urls.pop(), Some(bdata!("http://w")));
+        assert_eq!(urls.get_index(1), Some(&bdata!("http://z")));
+        *urls.get_index_mut(0).unwrap() = bdata!("http://v");
+        assert_eq!(urls.get_index(2), None);
+
+        let expected = b"d8:announce8:http://b7:comment1:c\
+4:infod4:name5:a.txt7:privatei1ee8:url-listl8:http://v8:http://zee";
+        assert_eq!(super::stringify(&data).unwrap(), &expected[..]);
+
+        // 类型不符时不做任何事
+        let mut list = bdata!([1]);
+        assert_eq!(list.insert("a", 1), None);
+        assert_eq!(list.remove("a"), None);
+        assert_eq!(list.get_index(0), Some(&bdata!(1)));
+        let mut dict = bdata!({"a" => 1});
+        assert!(dict.push(2).is_none());
+        assert_eq!(dict.pop(), None);
+        assert_eq!(dict.get_index(0), None);
+        assert_eq!(list, bdata!([1]));
+        assert_eq!(dict, bdata!({"a" => 1}));
+    }
+
+    #[test]
+    fn entry_test() {
+        let mut data = bdata!({"announce" => "http://old", "info" => {}});
+        // 已存在的键
+        data.entry("announce")
+            .and_modify(|v| *v = bdata!("http://new"))
+            .or_insert_with(|| bdata!("unused"));
+        // 不存在的键
+        let list = data.entry("announce-list").or_insert_list();
+        list.as_list_mut().unwrap().push(bdata!(["http://a"]));
+        let info = data.entry(String::from("info")).or_insert(1);
+        info.entry(&b"length"[..]).or_insert(5);
+        assert_eq!(
+            data,
+            bdata!({
+                "announce" => "http://new",
+                "announce-list" => [["http://a"]],
+                "info" => {"length" => 5},
+            })
+        );
+
+        assert_eq!(data.entry("info").key(), b"info");
+        match data.entry("info").into_entry() {
+            Entry::Occupied(e) => assert_eq!(e.get(), &bdata!({"length" => 5})),
+            Entry::Vacant(_) => panic!("info should exist"),
+        }
+        assert!(data.entry("name").or_insert("").is_string());
+        assert!(data.contains_key("name"));
+
+        assert_eq!(
+            panic_message(|| {
+                bdata!([]).entry("a");
+            }),
+            "cannot index list with key \"a\""
+        );
+    }
+
+    #[test]
+    fn entry_chain_test() {
+        let mut report = bdata!({});
+        let peers = [("peer1", 3), ("peer2", 1), ("peer1", 2)];
+        for (peer, n) in peers.iter() {
+            report
+                .entry("stats")
+                .or_insert_dict()
+                .entry("peers")
+                .or_insert_dict()
+                .entry(*peer)
+                .and_modify(|v| *v = bdata!(v.as_number().unwrap() + n))
+                .or_insert(*n);
+            report.entry("log").or_insert_list().push(*peer);
+        }
+        report
+            .entry("stats")
+            .or_insert_dict()
+            .entry("total")
+            .or_insert_with(|| bdata!(6));
+        let expected = bdata!({
+            "log" => ["peer1", "peer2", "peer1"],
+            "stats" => {
+                "peers" => {"peer1" => 5, "peer2" => 1},
+                "total" => 6,
+            },
+        });
+        assert_eq!(report, expected);
+    }
+
+    #[test]
+    fn is_type_test() {
+        let values = [
+            bdata!(""),
+            bdata!(1),
+            BData::BigNumber(b"18446744073709551616".to_vec()),
+            bdata!([]),
+            bdata!({}),
+        ];
+        let expected = [
+            [true, false, false, false],
+            [false, true, false, false],
+            [false, true, false, false],
+            [false, false, true, false],
+            [false, false, false, true],
+        ];
+        for (v, e) in values.iter().zip(expected.iter()) {
+            assert_eq!([v.is_string(), v.is_number(), v.is_list(), v.is_dict()], *e);
+        }
+    }
+
+    #[test]
+    fn kind_test() {
+        let values = [
+            (bdata!(""), BKind::Bytes, "bytes"),
+            (bdata!(-1), BKind::Number, "number"),
+            (
+                BData::BigNumber(b"18446744073709551616".to_vec()),
+                BKind::Number,
+                "big number",
+            ),
+            (bdata!([1]), BKind::List, "list"),
+            (bdata!({}), BKind::Dict, "dict"),
+        ];
+        for (v, kind, name) in values.iter() {
+            assert_eq!(v.kind(), *kind);
+            assert_eq!(v.type_name(), *name);
+            assert_eq!(v.is_number(), v.kind() == BKind::Number);
+        }
+    }
+
+    #[test]
+    fn clone_hash_test() {
+        let data = bdata!({"a" => [1, "x", {"b" => []}], "c" => -2});
+        let copy = data.clone();
+        assert_eq!(copy, data);
+
+        let mut set = HashSet::new();
+        assert!(set.insert(data));
+        assert!(!set.insert(copy));
+        assert!(set.insert(bdata!({"a" => [1, "x", {"b" => [0]}], "c" => -2})));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn hash_sort_consistency_test() {
+        // 解析得到的数据与按不同顺序构建的数据相等, 哈希也相同
+        let parsed = super::parse(b"d1:ali1e1:xe1:bd1:ci2eee").unwrap();
+        let built: BData = vec![
+            ("b", bdata!({"c" => 2})),
+            ("a", BData::List(vec![1.into(), "x".into()])),
+        ]
+        .into_iter()
+        .collect();
+        let mut set = HashSet::new();
+        set.insert(parsed.clone());
+        assert!(set.contains(&built));
+        assert!(!set.insert(built));
+
+        // 排序结果与初始顺序无关
+        let values = vec![
+            bdata!({"b" => 1}),
+            bdata!("b"),
+            bdata!([1, 2]),
+            BData::BigNumber(b"99999999999999999999".to_vec()),
+            bdata!(-1),
+            bdata!({"a" => 2}),
+            bdata!("a"),
+            bdata!([1]),
+            parsed,
+        ];
+        let mut sorted = values.clone();
+        sorted.sort();
+        let mut reversed: Vec<BData> = values.into_iter().rev().collect();
+        reversed.sort();
+        assert_eq!(sorted, reversed);
+        assert_eq!(sorted[0], bdata!(-1));
+        assert_eq!(sorted[2..4], [bdata!("a"), bdata!("b")]);
+        assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn display_test() {
+        let src = "d4:listl1:ai-3ee4:name5:a.txt3:numi42ee";
+        let data = super::parse_str(src).unwrap();
+        assert_eq!(data.to_string(), src);
+        assert_eq!(format!("{}", BData::Number(-1)), "i-1e");
+
+        let data = BData::BString(b"a\xffb".to_vec());
+        assert_eq!(data.to_string(), "3:a\u{fffd}b");
+
+        // 不合法的 `BigNumber` 不会导致 `to_string` panic
+        let data = bdata!([
+            BData::BigNumber(b"--1".to_vec()),
+            BData::BigNumber(b"\xff".to_vec())
+        ]);
+        assert!(super::stringify(&data).is_err());
+        assert_eq!(data.to_string(), "li--1ei\u{fffd}ee");
+        assert_eq!(format!("{}", bdata!({"k" => "\u{e9}"})), "d1:k2:\u{e9}e");
+    }
+
+    #[test]
+    fn debug_test() {
+        let cases: [(BData, &str); 6] = [
+            (bdata!(""), r#"BString("")"#),
+            (bdata!("a \"b\"\n"), r#"BString("a \"b\"\n")"#),
+            (bdata!(&b"ab\xff"[..]), "BString(hex: 6162ff, len: 3)"),
+            (bdata!(&b"a\x00"[..]), "BString(hex: 6100, len: 2)"),
+            (
+                BData::BigNumber(b"18446744073709551616".to_vec()),
+                r#"BigNumber("18446744073709551616")"#,
+            ),
+            (
+                bdata!({"a" => [1, "x"], &b"\x80"[..] => {}}),
+                r#"Dict({"a": List([Number(1), BString("x")]), hex: 80, len: 1: Dict({})})"#,
+            ),
+        ];
+        for (data, expected) in cases.iter() {
+            assert_eq!(format!("{:?}", data), *expected);
+        }
+
+        let data = BData::BString(vec![b'a'; 100]);
+        let expected = format!("BString({:?}…, len: 100)", "a".repeat(64));
+        assert_eq!(format!("{:?}", data), expected);
+        let data = BData::BString(vec![0xff; 100]);
+        let expected = format!("BString(hex: {}…, len: 100)", "ff".repeat(64));
+        assert_eq!(format!("{:?}", data), expected);
+        // 不会在字符中间截断
+        let data = BData::from("\u{4e2d}".repeat(30));
+        let expected = format!("BString({:?}…, len: 90)", "\u{4e2d}".repeat(21));
+        assert_eq!(format!("{:?}", data), expected);
+    }
+
+    #[test]
+    fn ord_test() {
+        // 按类型顺序排列, 每种类型一个值
+        let values = [
+            BData::BigNumber(b"-99999999999999999999".to_vec()),
+            BData::Number(i64::MAX),
+            BData::BigNumber(b"99999999999999999999".to_vec()),
+            BData::BString(Vec::new()),
+            BData::List(Vec::new()),
+            BData::Dict(Map::new()),
+        ];
+        for (i, a) in values.iter().enumerate() {
+            for (j, b) in values.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{:?} {:?}", a, b);
+            }
+        }
+
+        assert!(bdata!(-1) < bdata!(2));
+        assert!(bdata!("ab") < bdata!("b"));
+        assert!(bdata!("a") < bdata!("ab"));
+        assert!(bdata!([1, 2]) < bdata!([1, 3]));
+        assert!(bdata!([1]) < bdata!([1, 0]));
+        assert!(bdata!({"a" => 2}) < bdata!({"b" => 1}));
+        assert!(bdata!({"a" => 1}) < bdata!({"a" => 2}));
+        assert!(bdata!({"a" => 1}) < bdata!({"a" => 1, "b" => 0}));
+        assert_eq!(
+            bdata!({"a" => [1]}).cmp(&bdata!({"a" => [1]})),
+            Ordering::Equal
+        );
+
+        // 整数按数值排列, `Number` 与 `BigNumber` 交错
+        let big = |s: &str| BData::BigNumber(s.as_bytes().to_vec());
+        let integers = [
+            big("-100000000000000000000"),
+            big("-99999999999999999999"),
+            big("-10"),
+            BData::Number(-9),
+            big("-2"),
+            BData::Number(-1),
+            BData::Number(0),
+            big("0"),
+            big("9"),
+            BData::Number(10),
+            big("010"),
+            big("10"),
+            big("99999999999999999999"),
+            big("100000000000000000000"),
+        ];
+        for (i, a) in integers.iter().enumerate() {
+            for (j, b) in integers.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{:?} {:?}", a, b);
+                assert_eq!(a.cmp(b) == Ordering::Equal, a == b);
+            }
+        }
+        assert!(big("-0") > BData::Number(0));
+        assert!(big("-0") < big("1"));
+
+        let set: BTreeSet<BData> = vec![bdata!("x"), bdata!(1), bdata!([]), bdata!(1)]
+            .into_iter()
+            .collect();
+        let sorted: Vec<BData> = set.into_iter().collect();
+        assert_eq!(sorted, vec![bdata!(1), bdata!("x"), bdata!([])]);
+    }
+
+    #[test]
+    fn default_test() {
+        #[derive(Default)]
+        struct Report {
+            data: BData,
+        }
+        let report = Report::default();
+        assert_eq!(report.data, bdata!(""));
+        assert_eq!(super::stringify(&report.data).unwrap(), b"0:");
+
+        let mut data = bdata!({"a" => [1]});
+        let a = std::mem::take(&mut data["a"]);
+        assert_eq!(a, bdata!([1]));
+        assert_eq!(data, bdata!({"a" => ""}));
+    }
+
+    #[test]
+    fn from_test() {
+        assert_eq!(BData::from("abc"), BData::BString(b"abc".to_vec()));
+        assert_eq!(BData::from(String::from("abc")), BData::from("abc"));
+        assert_eq!(BData::from(&b"\xff"[..]), BData::BString(b"\xff".to_vec()));
+        assert_eq!(
+            BData::from(b"\xff".to_vec()),
+            BData::BString(b"\xff".to_vec())
+        );
+        let n: BData = 42.into();
+        assert_eq!(n, BData::Number(42));
+        assert_eq!(BData::from(-7i64), BData::Number(-7));
+
+        let list = BData::from(vec![
+            BData::from("spam"),
+            42.into(),
+            Vec::<BData>::new().into(),
+        ]);
+        assert_eq!(super::stringify(&list).unwrap(), b"l4:spami42elee".to_vec());
+
+        let mut m = BTreeMap::new();
+        m.insert(b"b".to_vec(), BData::from(1));
+        m.insert(b"a".to_vec(), BData::from("x"));
+        let dict = BData::from(m);
+        let encoded = super::stringify(&dict).unwrap();
+        assert_eq!(encoded, b"d1:a1:x1:bi1ee".to_vec());
+        assert_eq!(super::parse(&encoded).unwrap(), dict);
+    }
+
+    #[test]
+    fn dict_builder_test() {
+        let info = DictBuilder::new()
+            .entry("piece length", 16384)
+            .entry(String::from("name"), "a.txt")
+            .entry(&b"pieces"[..], vec![0u8, 0xff])
+            .entry(b"files".to_vec(), vec![bdata!(1)])
+            .build();
+        let data = DictBuilder::new()
+            .entry("info", info)
+            .entry("announce", "http://t")
+            .entry("announce", "http://tracker")
+            .build();
+        let encoded = super::stringify(&data).unwrap();
+        assert_eq!(
+            encoded,
+            &b"d8:announce14:http://tracker4:infod5:filesli1ee4:name5:a.txt\
+               12:piece lengthi16384e6:pieces2:\x00\xffee"[..]
+        );
+        assert_eq!(super::parse(&encoded).unwrap(), data);
+        assert_eq!(DictBuilder::new().build(), bdata!({}));
+    }
+
+    #[test]
+    fn from_iter_test() {
+        let list: BData = vec![1i64, 2, 3].into_iter().map(BData::from).collect();
+        assert_eq!(super::stringify(&list).unwrap(), b"li1ei2ei3ee");
+        let empty: BData = Vec::<BData>::new().into_iter().collect();
+        assert_eq!(empty, bdata!([]));
+
+        let dict: BData = vec![("b", 1i64.into()), ("a", "x".into())]
+            .into_iter()
+            .collect();
+        assert_eq!(super::stringify(&dict).unwrap(), b"d1:a1:x1:bi1ee");
+
+        // 重复的键保留最后一个值
+        let dict: BData = vec![
+            (String::from("k"), bdata!(1)),
+            (String::from("k"), bdata!(2)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(dict, bdata!({"k" => 2}));
+        let dict: BData = vec![(b"\xff".to_vec(), bdata!([]))].into_iter().collect();
+        assert_eq!(super::stringify(&dict).unwrap(), b"d1:\xfflee");
+
+        let mut m = BTreeMap::new();
+        m.insert(String::from("name"), bdata!("a"));
+        m.insert(String::from("length"), bdata!(5));
+        assert_eq!(
+            super::stringify(&BData::from(m)).unwrap(),
+            b"d6:lengthi5e4:name1:ae"
+        );
+    }
+
+    #[test]
+    fn parse_dict_duplicate_key_test() {
+        let v = super::parse(b"d1:a1:x1:a1:ye");
+        match v {
+            Err(ParseErr::DuplicateKey { key, offset }) => {
+                assert_eq!(key, b"a");
+                assert_eq!(offset, 7);
+            }
+            _ => panic!("expected DuplicateKey, got {:?}", v),
+        }
+        let v = super::parse(b"ld1:ai1eed1:ad1:ai1e1:ai2eee");
+        assert!(matches!(v, Err(ParseErr::DuplicateKey { offset: 20, .. })));
+
+        let opts = ParseOptions {
+            duplicate_keys: DuplicateKeyPolicy::KeepLast,
+            ..Default::default()
+        };
+        let data = super::parse_with_options(b"d1:a1:x1:a1:ye", &opts).unwrap();
+        let mut m = Map::new();
+        m.insert(b"a".to_vec(), BData::BString(b"y".to_vec()));
+        assert_eq!(data, BData::Dict(m));
+    }
+
+    #[test]
+    fn duplicate_key_policy_test() {
+        let src = b"ld1:ai1e1:ai2e1:bd1:ci3e1:ci4eeee";
+        let expected = |a: i64, c: i64| {
+            let mut inner = Map::new();
+            inner.insert(b"c".to_vec(), BData::Number(c));
+            let mut m = Map::new();
+            m.insert(b"a".to_vec(), BData::Number(a));
+            m.insert(b"b".to_vec(), BData::Dict(inner));
+            BData::List(vec![BData::Dict(m)])
+        };
+
+        let mut opts = ParseOptions {
+            duplicate_keys: DuplicateKeyPolicy::KeepLast,
+            ..Default::default()
+        };
+        assert_eq!(
+            super::parse_with_options(src, &opts).unwrap(),
+            expected(2, 4)
+        );
+
+        opts.duplicate_keys = DuplicateKeyPolicy::KeepFirst;
+        assert_eq!(
+            super::parse_with_options(src, &opts).unwrap(),
+            expected(1, 3)
+        );
+
+        opts.duplicate_keys = DuplicateKeyPolicy::Error;
+        let v = super::parse_with_options(src, &opts);
+        assert!(matches!(v, Err(ParseErr::DuplicateKey { offset: 8, .. })));
+        let v = super::parse_with_options(b"d1:bd1:ci3e1:ci4eee", &opts);
+        assert!(matches!(v, Err(ParseErr::DuplicateKey { offset: 11, .. })));
+    }
+
+    #[test]
+    fn parse_dict_unsorted_keys_test() {
+        let src = b"d1:b1:x1:a1:ye";
+        assert!(super::parse(src).is_ok());
+
+        let opts = ParseOptions {
+            require_sorted_keys: true,
+            ..Default::default()
+        };
+        let v = super::parse_with_options(src, &opts);
+        assert!(matches!(v, Err(ParseErr::UnsortedKeys { offset: 7 })));
+        // 按原始字节比较, 0xff 大于任何 ASCII 字节
+        assert!(super::parse_with_options(b"d1:a0:1:\xff0:e", &opts).is_ok());
+        let v = super::parse_with_options(b"d1:ad1:\xff0:1:b0:ee", &opts);
+        assert!(matches!(v, Err(ParseErr::UnsortedKeys { offset: 10 })));
+        let v = super::parse_with_options(b"d2:ab0:1:a0:e", &opts);
+        assert!(matches!(v, Err(ParseErr::UnsortedKeys { offset: 7 })));
+
+        // 重复的键同样不满足严格升序
+        let opts = ParseOptions {
+            require_sorted_keys: true,
+            duplicate_keys: DuplicateKeyPolicy::KeepLast,
+            ..Default::default()
+        };
+        let v = super::parse_with_options(b"ld1:a0:1:a0:ee", &opts);
+        assert!(matches!(v, Err(ParseErr::UnsortedKeys { offset: 7 })));
+    }
+
+    fn assert_stringify(s: &str, assert_s: Vec<u8>) {
+        if let Ok(data) = super::parse_str(s) {
+            let stringify = super::stringify(&data);
+            println!("parse: {}", s);
+            if let Ok(st) = stringify {
+                assert_eq!(st, assert_s);
+            } else {
+                panic!("stringify error!");
+            }
+        } else {
+            panic!("parse error!");
         }
     }
 
@@ -431,4 +4146,619 @@ mod test {
             .to_vec();
         assert_stringify(s, assert_s);
     }
+
+    #[test]
+    fn stringify_err_test() {
+        let ok = super::stringify(&BData::BigNumber(b"-18446744073709551616".to_vec()));
+        assert_eq!(ok.unwrap(), b"i-18446744073709551616e");
+
+        for num in [&b""[..], b"-", b"-0", b"012", b"1x", b"1e"].iter() {
+            let data = BData::List(vec![bdata!({"a" => BData::BigNumber(num.to_vec())})]);
+            assert_eq!(
+                super::stringify(&data),
+                Err(EncodeError::InvalidBigNumber(num.to_vec()))
+            );
+        }
+        let e = super::stringify(&BData::BigNumber(b"1x".to_vec())).unwrap_err();
+        assert_eq!(e.to_string(), "invalid big integer \"1x\"");
+    }
+
+    #[test]
+    fn stringify_nested_test() {
+        let src = "d1:ald1:bl1:cd1:dli1eleeeee0:e1:edee";
+        let data = super::parse_str(src).unwrap();
+        assert_eq!(super::stringify(&data).unwrap(), src.as_bytes());
+
+        // 任意层级中的错误都会返回到最外层
+        let bad = BData::BigNumber(b"--1".to_vec());
+        let mut data = bad.clone();
+        for i in 0..20 {
+            data = if i % 2 == 0 {
+                bdata!(["x", data, {}])
+            } else {
+                bdata!({"a" => 1, "b" => data})
+            };
+        }
+        assert_eq!(
+            super::stringify(&data),
+            Err(EncodeError::InvalidBigNumber(b"--1".to_vec()))
+        );
+        let e: Box<dyn Error> = Box::new(super::stringify(&bad).unwrap_err());
+        assert_eq!(e.to_string(), "invalid big integer \"--1\"");
+    }
+
+    #[test]
+    fn stringify_raw_key_order_test() {
+        // `\xff` 按字符串处理时为 U+FFFD, 排在 U+FFFF (`\xef\xbf\xbf`) 之前, 按字节则在之后
+        let keys: [&[u8]; 5] = [
+            b"\xff",
+            "\u{ffff}".as_bytes(),
+            b"z",
+            "\u{e9}".as_bytes(),
+            b"Z",
+        ];
+        let mut data = Map::new();
+        for (i, k) in keys.iter().enumerate() {
+            data.insert(k.to_vec(), BData::Number(i as i64));
+        }
+        let encoded = super::stringify(&BData::Dict(data)).unwrap();
+        let expected = b"d1:Zi4e1:zi2e2:\xc3\xa9i3e3:\xef\xbf\xbfi1e1:\xffi0ee";
+        assert_eq!(encoded, &expected[..]);
+
+        let mut lossy: Vec<String> = keys
+            .iter()
+            .map(|k| String::from_utf8_lossy(k).into())
+            .collect();
+        lossy.sort();
+        assert_eq!(lossy[3], "\u{fffd}");
+        assert!(super::parse_strict(&encoded).is_ok());
+    }
+
+    #[test]
+    fn stringify_into_test() {
+        let data = bdata!({"a" => [1, "xy"], "b" => {}});
+        let mut out = b"prefix".to_vec();
+        assert_eq!(super::stringify_into(&data, &mut out).unwrap(), 19);
+        assert_eq!(out, b"prefixd1:ali1e2:xye1:bdee");
+
+        out.clear();
+        super::stringify_into(&BData::Number(-3), &mut out).unwrap();
+        super::stringify_into(&bdata!("z"), &mut out).unwrap();
+        assert_eq!(out, b"i-3e1:z");
+
+        // 出错时不保留写入了一部分的数据
+        let bad = bdata!([1, BData::BigNumber(b"x".to_vec())]);
+        assert!(super::stringify_into(&bad, &mut out).is_err());
+        assert_eq!(out, b"i-3e1:z");
+
+        // 多个数据写入同一个缓冲区后依次解析
+        let values = [bdata!({"q" => "ping"}), bdata!(["a", -1]), bdata!(7)];
+        out.clear();
+        let mut ends = Vec::new();
+        for v in values.iter() {
+            let n = super::stringify_into(v, &mut out).unwrap();
+            ends.push(out.len());
+            assert_eq!(n, super::encoded_len(v));
+        }
+        assert_eq!(super::parse_all(&out).unwrap(), values.to_vec());
+        let mut it = super::values(&out);
+        for end in ends {
+            it.next().unwrap().unwrap();
+            assert_eq!(it.offset(), end);
+        }
+    }
+
+    #[test]
+    fn to_writer_test() {
+        let data = bdata!({
+            "info" => {"pieces" => vec![0u8; 1000], "length" => 7},
+            "list" => [BData::BigNumber(b"-99999999999999999999".to_vec()), "x"],
+        });
+        let mut out = Vec::new();
+        super::to_writer(&data, &mut out).unwrap();
+        assert_eq!(out, super::stringify(&data).unwrap());
+
+        let mut w = io::BufWriter::new(Vec::new());
+        super::to_writer(&BData::Number(5), &mut w).unwrap();
+        assert_eq!(w.into_inner().unwrap(), b"i5e");
+
+        let bad = bdata!([BData::BigNumber(b"-".to_vec())]);
+        let e = super::to_writer(&bad, &mut out).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        let inner = e.into_inner().unwrap().downcast::<EncodeError>().unwrap();
+        assert_eq!(*inner, EncodeError::InvalidBigNumber(b"-".to_vec()));
+    }
+
+    #[test]
+    fn encoded_len_test() {
+        let values = [
+            bdata!(""),
+            bdata!(vec![0u8; 9]),
+            bdata!(vec![0u8; 10]),
+            bdata!(vec![0u8; 1000]),
+            bdata!(0),
+            bdata!(9),
+            bdata!(-10),
+            BData::Number(i64::MIN),
+            BData::Number(i64::MAX),
+            BData::BigNumber(b"-99999999999999999999".to_vec()),
+            bdata!([]),
+            bdata!({}),
+            bdata!({"a" => [1, [[], {"b" => -22}]], "" => "x", &b"\xff"[..] => {}}),
+        ];
+        for v in values.iter() {
+            assert_eq!(super::encoded_len(v), super::stringify(v).unwrap().len());
+        }
+        for n in [0, 7, -10, 1_000_000, i64::MIN, i64::MAX].iter() {
+            let encoded = super::stringify(&BData::Number(*n)).unwrap();
+            assert_eq!(encoded, format!("i{}e", n).into_bytes());
+        }
+
+        let mut deep = bdata!({"" => -1});
+        for i in 0..100 {
+            deep = bdata!([i, deep, ""]);
+        }
+        let encoded = super::stringify(&deep).unwrap();
+        assert_eq!(super::encoded_len(&deep), encoded.len());
+        assert!(encoded.capacity() >= encoded.len());
+
+        // 只分配一次输出的缓冲区. 开启 `preserve-order` 时排序字典的键需要分配, 因此不含字典
+        let mut nested = bdata!([-1, "abc"]);
+        for i in 0..100 {
+            nested = bdata!([i, nested, vec![7u8; i as usize]]);
+        }
+        let before = ALLOCATIONS.with(Cell::get);
+        let out = super::stringify(&nested).unwrap();
+        assert_eq!(ALLOCATIONS.with(Cell::get) - before, 1);
+        assert_eq!(out.len(), super::encoded_len(&nested));
+        // 与逐个写入 writer 的结果相同
+        let mut out = Vec::new();
+        super::to_writer(&deep, &mut out).unwrap();
+        assert_eq!(out, encoded);
+        assert_eq!(super::parse(&encoded).unwrap(), deep);
+    }
+
+    thread_local! {
+        /// 当前线程的内存分配次数
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// 统计每个线程的分配次数, 用于检查编码时的分配
+    struct CountingAlloc;
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+    /// 测试专用的临时目录, 每个测试使用不同的名称
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("fraux_rs_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn file_test() {
+        let dir = temp_dir("file_test");
+        let path = dir.join("a.torrent");
+        let data = bdata!({
+            "announce" => "http://t",
+            "info" => {"name" => "a", "pieces" => &b"\x00\xff"[..], "files" => [{"length" => 1}]},
+        });
+        super::write_file(&path, &data).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), super::stringify(&data).unwrap());
+        assert_eq!(super::parse_file(&path).unwrap(), data);
+
+        // 覆盖已有的文件
+        super::write_file(&path, &bdata!(1)).unwrap();
+        assert_eq!(super::parse_file(&path).unwrap(), bdata!(1));
+
+        // 编码失败时原有的文件保持不变, 也不留下临时文件
+        let bad = bdata!([BData::BigNumber(b"x".to_vec())]);
+        let e = super::write_file(&path, &bad).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read(&path).unwrap(), b"i1e");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let v = super::parse_file(dir.join("missing"));
+        assert!(matches!(v, Err(ParseErr::IoError(_))));
+        fs::write(&path, b"i1ei2e").unwrap();
+        let v = super::parse_file(&path);
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset: 3 })));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_file_concurrent_test() {
+        let dir = temp_dir("write_file_concurrent_test");
+        let path = dir.join("a.torrent");
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    let data = bdata!({"id" => i, "pieces" => vec![i as u8; 64 * 1024]});
+                    for _ in 0..20 {
+                        super::write_file(&path, &data).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        // 文件是某一次完整的写入, 没有留下临时文件
+        let data = super::parse_file(&path).unwrap();
+        let id = data["id"].as_number().unwrap();
+        assert_eq!(data["pieces"], bdata!(vec![id as u8; 64 * 1024]));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 写入 `remaining` 个字节之后开始返回错误
+    struct FailAfter {
+        written: Vec<u8>,
+        remaining: usize,
+    }
+
+    impl io::Write for FailAfter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"));
+            }
+            let n = buf.len().min(self.remaining);
+            self.written.extend_from_slice(&buf[..n]);
+            self.remaining -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn to_writer_err_test() {
+        let data = bdata!({"a" => [1, "xyz", {"b" => vec![0u8; 100]}], "c" => -1});
+        let encoded = super::stringify(&data).unwrap();
+        for n in 0..encoded.len() {
+            let mut w = FailAfter {
+                written: Vec::new(),
+                remaining: n,
+            };
+            let e = super::to_writer(&data, &mut w).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+            assert_eq!(w.written, &encoded[..n]);
+        }
+        let mut w = FailAfter {
+            written: Vec::new(),
+            remaining: encoded.len(),
+        };
+        super::to_writer(&data, &mut w).unwrap();
+        assert_eq!(w.written, encoded);
+    }
+
+    #[test]
+    fn stringify_preserve_order_test() {
+        let src = b"d1:bi1e1:ad1:di2e1:ci3eee";
+        let data = super::parse(src).unwrap();
+        let sorted = b"d1:ad1:ci3e1:di2ee1:bi1ee";
+        assert_eq!(super::stringify(&data).unwrap(), &sorted[..]);
+
+        let opts = super::EncodeOptions {
+            preserve_order: true,
+        };
+        let out = super::stringify_with(&data, &opts).unwrap();
+        if cfg!(feature = "preserve-order") {
+            assert_eq!(out, &src[..]);
+            let keys: Vec<_> = data.iter_dict().unwrap().map(|(k, _)| k).collect();
+            assert_eq!(keys, [b"b", b"a"]);
+        } else {
+            assert_eq!(out, &sorted[..]);
+        }
+        // 键的顺序不影响比较与哈希
+        let other = super::parse(sorted).unwrap();
+        assert_eq!(data, other);
+        assert_eq!(data.cmp(&other), Ordering::Equal);
+        assert_eq!([data, other].iter().collect::<HashSet<_>>().len(), 1);
+    }
+
+    #[test]
+    fn stringify_dict_key_order_test() {
+        // 按 UTF-16 编码比较时 "\u{10000}" 排在 "\u{ff61}" 之前, 按原始字节比较时在之后
+        let mut m = Map::new();
+        m.insert("\u{10000}".as_bytes().to_vec(), BData::Number(1));
+        m.insert("\u{ff61}".as_bytes().to_vec(), BData::Number(2));
+        m.insert("z".as_bytes().to_vec(), BData::Number(3));
+        m.insert(b"\xff".to_vec(), BData::Number(4));
+        m.insert("é".as_bytes().to_vec(), BData::Number(5));
+        let out = super::stringify(&BData::Dict(m)).unwrap();
+
+        let mut expected = b"d1:zi3e2:\xc3\xa9i5e".to_vec();
+        expected.extend_from_slice(b"3:\xef\xbd\xa1i2e4:\xf0\x90\x80\x80i1e1:\xffi4ee");
+        assert_eq!(out, expected);
+        let opts = ParseOptions {
+            require_sorted_keys: true,
+            ..Default::default()
+        };
+        assert!(super::parse_with_options(&out, &opts).is_ok());
+    }
+
+    #[test]
+    fn parse_prefix_test() {
+        let sources = [
+            "3:abc",
+            "0:",
+            "i-32e",
+            "le",
+            "de",
+            "l3:abci32el2:abee",
+            "d2:k13:abc2:k2l3:defi-23eee",
+            "d1:ad1:bl1:cdeeee",
+        ];
+        for s in sources.iter() {
+            let mut src = s.as_bytes().to_vec();
+            src.extend_from_slice(b"trailing\x00\xff");
+            let (data, consumed) = super::parse_prefix(&src).expect("parse_prefix failed");
+            assert_eq!(consumed, s.len());
+            assert_eq!(super::stringify(&data).unwrap().len(), consumed);
+            assert_eq!(&src[consumed..], b"trailing\x00\xff");
+        }
+    }
+
+    #[test]
+    fn parse_complete_test() {
+        assert_eq!(
+            super::parse_complete(b"l3:abci32ee").unwrap(),
+            BData::List(vec![BData::BString(b"abc".to_vec()), BData::Number(32)])
+        );
+        let v = super::parse_complete(b"3:abcd");
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset: 5 })));
+        let v = super::parse_complete(b"i1e\n");
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset: 3 })));
+        let v = super::parse_complete(b"");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 0, .. })));
+        let v = super::parse(b"l1:ae1:b");
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset: 5 })));
+    }
+
+    #[test]
+    fn values_test() {
+        let src = b"3:abci-1eld1:ai1eee";
+        let all = super::parse_all(src).expect("parse_all failed");
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0], BData::BString(b"abc".to_vec()));
+        assert_eq!(all[1], BData::Number(-1));
+
+        let mut values = super::values(b"i1e3:abci2e5:ab");
+        assert_eq!(values.next().unwrap().unwrap(), BData::Number(1));
+        assert_eq!(
+            values.next().unwrap().unwrap(),
+            BData::BString(b"abc".to_vec())
+        );
+        assert_eq!(values.next().unwrap().unwrap(), BData::Number(2));
+        assert!(matches!(
+            values.next(),
+            Some(Err(ParseErr::UnexpectedEof { offset: 15, .. }))
+        ));
+        assert_eq!(values.offset(), 11);
+        assert!(values.next().is_none());
+
+        let mut values = super::values(b"i1eli2eXe");
+        assert!(values.next().unwrap().is_ok());
+        assert!(matches!(
+            values.next(),
+            Some(Err(ParseErr::UnexpectedByte { offset: 7, .. }))
+        ));
+        assert_eq!(values.offset(), 3);
+
+        assert!(super::values(b"").next().is_none());
+        assert!(super::parse_all(b"").unwrap().is_empty());
+    }
+
+    /// 每次最多只返回一个字节的 reader, 用于模拟短读
+    struct ShortReader<R>(R);
+
+    impl<R: Read> Read for ShortReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+        }
+    }
+
+    fn large_dict_source() -> Vec<u8> {
+        let mut src = b"d".to_vec();
+        for i in 0..200 {
+            let key = format!("key{:04}", i);
+            let entry = format!(
+                "{}:{}l{}:{}i{}ee",
+                key.len(),
+                key,
+                24,
+                "abcdefghijklmnopqrstuvwx",
+                i
+            );
+            src.extend_from_slice(entry.as_bytes());
+        }
+        src.push(b'e');
+        src
+    }
+
+    #[test]
+    fn parse_reader_test() {
+        let src = large_dict_source();
+        assert!(src.len() > 4096 * 2);
+
+        let expected = super::parse(&src).expect("parse failed");
+        let data = super::parse_reader(Cursor::new(src.clone())).expect("parse_reader failed");
+        assert_eq!(data, expected);
+
+        let data = super::parse_reader(ShortReader(Cursor::new(src))).expect("short read failed");
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn parse_reader_large_test() {
+        // 跨越多次读取的长字符串, 每次读取都复制已缓存的部分会退化为平方复杂度
+        let data = bdata!({"a" => vec![7u8; 16 << 20], "b" => [1, vec![9u8; 3 << 20]]});
+        let src = super::stringify(&data).unwrap();
+        assert_eq!(super::parse_reader(Cursor::new(src)).unwrap(), data);
+    }
+
+    #[test]
+    fn parse_reader_by_ref_test() {
+        let torrent = bdata!({
+            "announce" => "http://t",
+            "info" => {"name" => "a", "pieces" => vec![7u8; 20 * 1000]},
+        });
+        let mut r = Cursor::new(super::stringify(&torrent).unwrap());
+        assert_eq!(super::parse_reader(&mut r).unwrap(), torrent);
+        assert_eq!(r.position() as usize, r.get_ref().len());
+    }
+
+    #[test]
+    fn parse_reader_err_test() {
+        let v = super::parse_reader(Cursor::new(b"5:ab".to_vec()));
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 4, .. })));
+        let v = super::parse_reader(Cursor::new(Vec::new()));
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 0, .. })));
+        let v = super::parse_reader(FailingReader);
+        assert!(matches!(v, Err(ParseErr::IoError(_))));
+
+        // 与 `parse` 相同, 不允许数据之后有多余的字节
+        let v = super::parse_reader(Cursor::new(b"i1ejunk".to_vec()));
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset: 3 })));
+        let mut src = large_dict_source();
+        let end = src.len();
+        src.extend_from_slice(&[b'x'; super::READER_BUF_SIZE]);
+        let v = super::parse_reader(Cursor::new(src[..end + 1].to_vec()));
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset }) if offset == end));
+
+        // 数据结束后不再读取, 之后的字节留在 reader 中
+        let mut r = ShortReader(Cursor::new(src));
+        assert!(super::parse_reader(&mut r).is_ok());
+        assert_eq!(r.0.position() as usize, end);
+        let r = Cursor::new(b"i1e".to_vec()).chain(BlockingReader);
+        assert_eq!(super::parse_reader(r).unwrap(), BData::Number(1));
+    }
+
+    /// 模拟没有更多数据的连接, 读取时直接 panic
+    struct BlockingReader;
+
+    impl Read for BlockingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            panic!("read blocks until the peer sends more data")
+        }
+    }
+
+    /// 曾经导致 panic 或栈溢出的输入, 以及各种截断的数据
+    const FUZZ_REGRESSIONS: [&[u8]; 16] = [
+        b"99999999999999999999:",
+        b"18446744073709551616:a",
+        b"18446744073709551615:",
+        b"i9223372036854775808e",
+        b"i-9223372036854775809e",
+        b"i--1e",
+        b"i-e",
+        b"i1-2e",
+        b"d0:",
+        b"d1:a",
+        b"di1ee",
+        b"l\xffe",
+        b"e",
+        b"",
+        b"0:0:",
+        b"d1:ad1:ad1:ad1:ae",
+    ];
+
+    /// 对一个输入调用所有解析入口, 只要求不 panic
+    fn parse_everything(src: &[u8]) {
+        let strict = ParseOptions::strict();
+        let big = ParseOptions {
+            big_numbers: true,
+            duplicate_keys: DuplicateKeyPolicy::KeepFirst,
+            max_total_bytes: 64,
+            ..Default::default()
+        };
+        let parsed = super::parse(src);
+        let _ = super::parse_with_options(src, &strict);
+        let _ = super::parse_with_options(src, &big);
+        let _ = super::parse_with_spans(src);
+        let _ = super::parse_all(src);
+        let _ = super::Tokenizer::new(src).count();
+        let _ = super::parse_reader(ShortReader(Cursor::new(src.to_vec())));
+
+        let mut p = super::Parser::new();
+        for chunk in src.chunks(3) {
+            let _ = p.push(chunk);
+        }
+        let _ = p.finish();
+
+        // 解析成功的数据编码后应当得到相同的数据
+        if let Ok(data) = parsed {
+            let encoded = super::stringify(&data).unwrap();
+            assert_eq!(super::encoded_len(&data), encoded.len());
+            assert_eq!(super::parse(&encoded).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn fuzz_regression_test() {
+        for src in FUZZ_REGRESSIONS.iter() {
+            parse_everything(src);
+        }
+        let v = super::parse(b"99999999999999999999:");
+        assert!(matches!(
+            v,
+            Err(ParseErr::IntegerOverflow { offset: 0, .. })
+        ));
+
+        // 默认的层数限制使深层嵌套在解析时就被拒绝
+        let deep = vec![b'l'; 1_000_000];
+        let v = super::parse(&deep);
+        assert!(matches!(v, Err(ParseErr::DepthLimitExceeded { .. })));
+        parse_everything(&deep[..10_000]);
+    }
+
+    #[test]
+    fn fuzz_random_test() {
+        const ALPHABET: &[u8] = b"ildeeee0123456789::--x";
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..20_000 {
+            let len = (next() % 24) as usize;
+            let src: Vec<u8> = (0..len)
+                .map(|_| ALPHABET[(next() % ALPHABET.len() as u64) as usize])
+                .collect();
+            parse_everything(&src);
+        }
+    }
 }
@@ -0,0 +1,115 @@
+/// 以字面量的形式构建 `BData`.
+///
+/// `{ key => value, ... }` 构建字典, `[ value, ... ]` 构建列表, 其余的值通过 `BData::from` 转换.
+/// 字典的键可以是任何实现了 `AsRef<[u8]>` 的值.
+///
+/// 字符串与整数:
+///
+/// ```
+/// use fraux_rs::{bdata, BData};
+/// assert_eq!(bdata!("abc"), BData::BString(b"abc".to_vec()));
+/// assert_eq!(bdata!(-42), BData::Number(-42));
+/// ```
+///
+/// 列表:
+///
+/// ```
+/// use fraux_rs::{bdata, BData};
+/// let list = bdata!(["a", 1, [], [2, -3]]);
+/// assert_eq!(fraux_rs::stringify(&list).unwrap(), b"l1:ai1eleli2ei-3eee");
+/// ```
+///
+/// 字典:
+///
+/// ```
+/// use fraux_rs::bdata;
+/// let data = bdata!({
+///     "name" => "file.txt",
+///     "length" => 1024,
+///     "files" => ["a", "b"],
+///     b"\xff" => {},
+/// });
+/// let expected = fraux_rs::parse(b"d5:filesl1:a1:be6:lengthi1024e4:name8:file.txt1:\xffdee");
+/// assert_eq!(data, expected.unwrap());
+/// ```
+#[macro_export]
+macro_rules! bdata {
+    // 列表的元素, 已转换的元素保存在方括号中
+    (@list [$($out:expr,)*]) => {
+        $crate::BData::List($crate::__private::vec![$($out,)*])
+    };
+    (@list [$($out:expr,)*] {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::bdata!(@list [$($out,)* $crate::bdata!({$($inner)*}),] $($($rest)*)?)
+    };
+    (@list [$($out:expr,)*] [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::bdata!(@list [$($out,)* $crate::bdata!([$($inner)*]),] $($($rest)*)?)
+    };
+    (@list [$($out:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bdata!(@list [$($out,)* $crate::BData::from($value),] $($($rest)*)?)
+    };
+
+    // 字典的键值对, 逐个插入 `$map`
+    (@dict $map:ident) => {};
+    (@dict $map:ident $key:expr => {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::bdata!(@insert $map $key, $crate::bdata!({$($inner)*}));
+        $crate::bdata!(@dict $map $($($rest)*)?);
+    };
+    (@dict $map:ident $key:expr => [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::bdata!(@insert $map $key, $crate::bdata!([$($inner)*]));
+        $crate::bdata!(@dict $map $($($rest)*)?);
+    };
+    (@dict $map:ident $key:expr => $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bdata!(@insert $map $key, $crate::BData::from($value));
+        $crate::bdata!(@dict $map $($($rest)*)?);
+    };
+    (@insert $map:ident $key:expr, $value:expr) => {
+        $map.insert(::core::convert::AsRef::<[u8]>::as_ref(&$key).to_vec(), $value);
+    };
+
+    ({$($inner:tt)*}) => {{
+        #[allow(unused_mut)]
+        let mut map = $crate::Map::new();
+        $crate::bdata!(@dict map $($inner)*);
+        $crate::BData::Dict(map)
+    }};
+    ([$($inner:tt)*]) => {
+        $crate::bdata!(@list [] $($inner)*)
+    };
+    ($value:expr) => {
+        $crate::BData::from($value)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::BData;
+    use crate::Map;
+
+    #[test]
+    fn bdata_macro_test() {
+        assert_eq!(bdata!({}), BData::Dict(Map::new()));
+        assert_eq!(bdata!([]), BData::List(Vec::new()));
+
+        let name = String::from("a.txt");
+        let data = bdata!({
+            "info" => {
+                "length" => 1024,
+                "name" => name.clone(),
+                "pieces" => [[1, {"x" => -1}], {}],
+            },
+            String::from("announce") => b"http://t".to_vec(),
+        });
+        let expected = crate::parse_str(
+            "d8:announce8:http://t4:infod6:lengthi1024e4:name5:a.txt6:piecesl\
+             li1ed1:xi-1eeedeeee",
+        )
+        .unwrap();
+        assert_eq!(data, expected);
+
+        let nested = bdata!([bdata!("x"), (1 + 2), {"k" => []}]);
+        assert_eq!(
+            crate::stringify(&nested).unwrap(),
+            b"l1:xi3ed1:kleee".to_vec()
+        );
+    }
+}
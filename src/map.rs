@@ -0,0 +1,49 @@
+//! 字典使用的映射类型.
+//!
+//! 默认为按键排序的 `BTreeMap`. 开启 `preserve-order` 时为保留插入顺序的 `IndexMap`,
+//! 解析得到的字典保持输入中的顺序. 两种情况下比较, 哈希以及默认的编码都按键的顺序进行
+
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "preserve-order"))]
+pub use alloc::collections::btree_map::Entry;
+#[cfg(feature = "preserve-order")]
+pub use indexmap::map::Entry;
+
+/// `BData::Dict` 使用的映射
+#[cfg(feature = "preserve-order")]
+pub type Map<K, V> = indexmap::IndexMap<K, V>;
+/// `BData::Dict` 使用的映射
+#[cfg(not(feature = "preserve-order"))]
+pub type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+#[cfg(all(feature = "serde", feature = "preserve-order"))]
+pub(crate) type Iter<'a, K, V> = indexmap::map::Iter<'a, K, V>;
+#[cfg(all(feature = "serde", not(feature = "preserve-order")))]
+pub(crate) type Iter<'a, K, V> = alloc::collections::btree_map::Iter<'a, K, V>;
+
+/// 按键的顺序遍历
+#[cfg(not(feature = "preserve-order"))]
+pub(crate) fn sorted<K: Ord, V>(map: &Map<K, V>) -> impl Iterator<Item = (&K, &V)> {
+    map.iter()
+}
+
+/// 按键的顺序遍历
+#[cfg(feature = "preserve-order")]
+pub(crate) fn sorted<K: Ord, V>(map: &Map<K, V>) -> impl Iterator<Item = (&K, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    entries.into_iter()
+}
+
+/// 删除键 `key`, 开启 `preserve-order` 时保持其余键的顺序
+#[cfg(not(feature = "preserve-order"))]
+pub(crate) fn remove<V>(map: &mut Map<Vec<u8>, V>, key: &[u8]) -> Option<V> {
+    map.remove(key)
+}
+
+/// 删除键 `key`, 开启 `preserve-order` 时保持其余键的顺序
+#[cfg(feature = "preserve-order")]
+pub(crate) fn remove<V>(map: &mut Map<Vec<u8>, V>, key: &[u8]) -> Option<V> {
+    map.shift_remove(key)
+}
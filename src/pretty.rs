@@ -0,0 +1,181 @@
+use crate::BData;
+use alloc::string::String;
+use core::fmt::Write;
+
+/// `BData::to_pretty_string` 的选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// 每层缩进的空格数, 默认为 2
+    pub indent: usize,
+    /// 不是 UTF-8 的字符串最多显示的字节数, 超出部分以 `…` 省略, 默认为 16
+    pub max_preview: usize,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> PrettyOptions {
+        PrettyOptions {
+            indent: 2,
+            max_preview: 16,
+        }
+    }
+}
+
+impl BData {
+    /// 转换为便于阅读的多行文本, 用于调试.
+    ///
+    /// UTF-8 字符串加引号显示, 其他字符串显示为长度与十六进制预览, 如 `<20 bytes: 0001…>`.
+    /// 输出格式仅用于阅读, 不能再解析.
+    ///
+    /// ```
+    /// use fraux_rs::{BData, PrettyOptions};
+    /// let data = fraux_rs::parse(b"d4:name1:a6:pieces3:\x00\x01\xffe").unwrap();
+    /// let opts = PrettyOptions { indent: 4, max_preview: 2 };
+    /// assert_eq!(
+    ///     data.to_pretty_string(&opts),
+    ///     "{\n    \"name\": \"a\",\n    \"pieces\": <3 bytes: 0001…>\n}"
+    /// );
+    /// ```
+    pub fn to_pretty_string(&self, opts: &PrettyOptions) -> String {
+        let mut out = String::new();
+        write_pretty(self, opts, 0, &mut out);
+        out
+    }
+}
+
+fn write_pretty(data: &BData, opts: &PrettyOptions, depth: usize, out: &mut String) {
+    match data {
+        BData::BString(s) => write_bytes(s, opts, out),
+        BData::Number(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        BData::BigNumber(n) => out.push_str(&String::from_utf8_lossy(n)),
+        BData::List(list) if list.is_empty() => out.push_str("[]"),
+        BData::List(list) => {
+            out.push('[');
+            for (i, v) in list.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(opts, depth + 1, out);
+                write_pretty(v, opts, depth + 1, out);
+            }
+            newline(opts, depth, out);
+            out.push(']');
+        }
+        BData::Dict(dict) if dict.is_empty() => out.push_str("{}"),
+        BData::Dict(dict) => {
+            out.push('{');
+            for (i, (k, v)) in dict.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(opts, depth + 1, out);
+                write_bytes(k, opts, out);
+                out.push_str(": ");
+                write_pretty(v, opts, depth + 1, out);
+            }
+            newline(opts, depth, out);
+            out.push('}');
+        }
+    }
+}
+
+fn newline(opts: &PrettyOptions, depth: usize, out: &mut String) {
+    out.push('\n');
+    out.push_str(&" ".repeat(opts.indent * depth));
+}
+
+fn write_bytes(s: &[u8], opts: &PrettyOptions, out: &mut String) {
+    if let Ok(s) = core::str::from_utf8(s) {
+        let _ = write!(out, "{:?}", s);
+        return;
+    }
+    let _ = write!(out, "<{} bytes: ", s.len());
+    for b in s.iter().take(opts.max_preview) {
+        let _ = write!(out, "{:02x}", b);
+    }
+    if s.len() > opts.max_preview {
+        out.push('…');
+    }
+    out.push('>');
+}
+
+#[cfg(test)]
+mod test {
+    use super::PrettyOptions;
+    use crate::BData;
+
+    fn torrent() -> BData {
+        let mut pieces = vec![0xa1, 0xb2, 0xc3];
+        pieces.resize(524288, 0xff);
+        bdata!({
+            "announce" => "http://tracker/announce",
+            "announce-list" => [["http://a"], []],
+            "comment" => "say \"hi\"\n",
+            "info" => {
+                "files" => [{"length" => 5, "path" => ["dir", "\u{4e2d}.txt"]}],
+                "name" => "dir",
+                "piece length" => 262144,
+                "pieces" => pieces,
+                "private" => {},
+            },
+            "size" => BData::BigNumber(b"-18446744073709551616".to_vec()),
+        })
+    }
+
+    #[test]
+    fn pretty_test() {
+        let expected = r#"{
+  "announce": "http://tracker/announce",
+  "announce-list": [
+    [
+      "http://a"
+    ],
+    []
+  ],
+  "comment": "say \"hi\"\n",
+  "info": {
+    "files": [
+      {
+        "length": 5,
+        "path": [
+          "dir",
+          "中.txt"
+        ]
+      }
+    ],
+    "name": "dir",
+    "piece length": 262144,
+    "pieces": <524288 bytes: a1b2c3ffffffffffffffffffffffffff…>,
+    "private": {}
+  },
+  "size": -18446744073709551616
+}"#;
+        assert_eq!(
+            torrent().to_pretty_string(&PrettyOptions::default()),
+            expected
+        );
+    }
+
+    #[test]
+    fn pretty_options_test() {
+        let data = bdata!({"e" => "", &b"\xff\x00"[..] => [&b"\x80\x02\x03"[..], 1]});
+        let opts = PrettyOptions {
+            indent: 1,
+            max_preview: 3,
+        };
+        assert_eq!(
+            data.to_pretty_string(&opts),
+            "{\n \"e\": \"\",\n <2 bytes: ff00>: [\n  <3 bytes: 800203>,\n  1\n ]\n}"
+        );
+        let opts = PrettyOptions {
+            indent: 0,
+            max_preview: 0,
+        };
+        assert_eq!(
+            data.to_pretty_string(&opts),
+            "{\n\"e\": \"\",\n<2 bytes: …>: [\n<3 bytes: …>,\n1\n]\n}"
+        );
+        assert_eq!(bdata!(-1).to_pretty_string(&opts), "-1");
+    }
+}
@@ -0,0 +1,284 @@
+use crate::token::Lexer;
+use crate::{BData, Builder, ParseErr, ParseOptions};
+use alloc::vec::Vec;
+use core::mem;
+
+/// 增量解析器, 数据可以分成任意大小的片段多次送入.
+///
+/// 解析器在字符串, 整数以及嵌套结构的中间都可以暂停, 等待下一个片段.
+/// 一个完整的数据解析完成后解析器会回到初始状态, 可以继续解析后续的数据.
+/// 出现错误后解析器同样会被重置.
+pub struct Parser {
+    lexer: Lexer,
+    builder: Builder,
+    /// 尚未组成完整 token 的字节
+    pending: Vec<u8>,
+    /// 下一次读取的第一个字节 (包括 `pending`) 在整个输入流中的位置
+    offset: usize,
+}
+
+impl Default for Parser {
+    fn default() -> Parser {
+        Parser::new()
+    }
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser::with_options(&ParseOptions::default())
+    }
+
+    pub fn with_options(opts: &ParseOptions) -> Parser {
+        Parser {
+            lexer: Lexer::with_options(opts),
+            builder: Builder::with_options(opts),
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// 送入一个数据片段.
+    ///
+    /// 数据完整时返回解析结果以及 `chunk` 中属于该数据的字节数,
+    /// 剩余的字节不会被读取, 由调用方自行处理. 数据尚不完整时返回 `Ok(None)`.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Option<(BData, usize)>, ParseErr> {
+        let prev = self.pending.len();
+        let res = if prev == 0 {
+            self.feed(chunk).map(|(data, pos)| match data {
+                Some(data) => Some((data, pos)),
+                None => {
+                    self.pending.extend_from_slice(&chunk[pos..]);
+                    None
+                }
+            })
+        } else {
+            // 将新的片段追加到残缺的 token 之后再读取, 只移除已经读取的部分
+            let mut buf = mem::take(&mut self.pending);
+            buf.extend_from_slice(chunk);
+            let res = self.feed(&buf);
+            match res {
+                Ok((None, pos)) => {
+                    buf.drain(..pos);
+                }
+                _ => buf.clear(),
+            }
+            self.pending = buf;
+            res.map(|(data, pos)| data.map(|data| (data, pos - prev)))
+        };
+
+        if res.is_err() {
+            self.reset();
+        }
+        res
+    }
+
+    /// 输入结束. 如果仍有未完成的数据返回 `ParseErr::UnexpectedEof`.
+    pub fn finish(&mut self) -> Result<(), ParseErr> {
+        let started = self.lexer.depth() > 0 || !self.pending.is_empty();
+        let err = ParseErr::UnexpectedEof {
+            expected: self.lexer.expected_at_end(&self.pending, 0),
+            offset: self.offset + self.pending.len(),
+        };
+        self.reset();
+        if started {
+            Err(err)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn reset(&mut self) {
+        self.lexer.reset();
+        self.builder.reset();
+        self.pending.clear();
+        self.offset = 0;
+    }
+
+    /// 读取 `src` 中完整的 token, 返回完整的数据 (如果有) 以及已经读取的字节数
+    fn feed(&mut self, src: &[u8]) -> Result<(Option<BData>, usize), ParseErr> {
+        let mut pos = 0;
+        self.lexer.base = self.offset;
+        while let Some((token, end)) = self.lexer.next_token(src, pos)? {
+            let offset = self.offset + pos;
+            pos = end;
+            if let Some(data) = self.builder.push(offset, token)? {
+                self.offset += pos;
+                return Ok((Some(data), pos));
+            }
+        }
+        self.offset += pos;
+        Ok((None, pos))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Parser;
+    use crate::{BData, ParseErr, ParseOptions};
+
+    const SOURCES: [&str; 7] = [
+        "3:abc",
+        "0:",
+        "i-32e",
+        "le",
+        "l3:abci32el2:abee",
+        "d2:k13:abc2:k2l3:defi-23eee",
+        "d1:ad1:bl1:cdeee1:bi7ee",
+    ];
+
+    fn push_all(p: &mut Parser, chunks: &[&[u8]]) -> Option<(BData, usize)> {
+        for chunk in chunks {
+            if let Some(v) = p.push(chunk).expect("push failed") {
+                return Some(v);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn push_split_test() {
+        for s in SOURCES.iter() {
+            let src = s.as_bytes();
+            let expected = crate::parse(src).unwrap();
+            for i in 0..=src.len() {
+                let mut p = Parser::new();
+                let (data, used) = push_all(&mut p, &[&src[..i], &src[i..]]).unwrap();
+                assert_eq!(data, expected);
+                if i < src.len() {
+                    assert_eq!(used, src.len() - i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn push_bytewise_test() {
+        for s in SOURCES.iter() {
+            let src = s.as_bytes();
+            let expected = crate::parse(src).unwrap();
+            let chunks: Vec<&[u8]> = src.chunks(1).collect();
+            let mut p = Parser::new();
+            let (data, used) = push_all(&mut p, &chunks).unwrap();
+            assert_eq!(data, expected);
+            assert_eq!(used, 1);
+            assert!(p.finish().is_ok());
+        }
+    }
+
+    #[test]
+    fn push_remainder_test() {
+        let mut p = Parser::new();
+        assert!(p.push(b"l3:a").unwrap().is_none());
+        let (data, used) = p.push(b"bcei1e2:xy").unwrap().unwrap();
+        assert_eq!(data, BData::List(vec![BData::BString(b"abc".to_vec())]));
+        assert_eq!(used, 3);
+
+        let (data, used) = p.push(b"i1e2:xy").unwrap().unwrap();
+        assert_eq!(data, BData::Number(1));
+        assert_eq!(used, 3);
+    }
+
+    #[test]
+    fn push_err_test() {
+        let mut p = Parser::new();
+        assert!(p.push(b"l3:ab").unwrap().is_none());
+        assert!(matches!(
+            p.finish(),
+            Err(ParseErr::UnexpectedEof {
+                expected: "string",
+                offset: 5
+            })
+        ));
+
+        let mut p = Parser::new();
+        assert!(p.push(b"l3:a").unwrap().is_none());
+        assert!(matches!(
+            p.push(b"bcXe"),
+            Err(ParseErr::UnexpectedByte {
+                found: b'X',
+                expected: "value or 'e'",
+                offset: 6
+            })
+        ));
+
+        let mut p = Parser::new();
+        assert!(matches!(
+            p.push(b"di1e"),
+            Err(ParseErr::NonStringDictKey { offset: 1 })
+        ));
+        assert!(matches!(
+            p.push(b"i-0e"),
+            Err(ParseErr::InvalidInteger { offset: 0, .. })
+        ));
+        assert_eq!(p.push(b"i1e").unwrap(), Some((BData::Number(1), 3)));
+    }
+
+    #[test]
+    fn push_sorted_keys_test() {
+        let opts = ParseOptions {
+            require_sorted_keys: true,
+            ..Default::default()
+        };
+        let mut p = Parser::with_options(&opts);
+        assert!(p.push(b"d1:ai1e1:bd1:").unwrap().is_none());
+        assert!(p.push(b"xi1e1:").unwrap().is_none());
+        assert!(matches!(
+            p.push(b"wi2eee"),
+            Err(ParseErr::UnsortedKeys { offset: 17 })
+        ));
+
+        let mut p = Parser::with_options(&opts);
+        let v = p.push(b"d1:ai1e1:bd1:wi2e1:xi1eee").unwrap();
+        assert!(matches!(v, Some((BData::Dict(_), 25))));
+    }
+
+    #[test]
+    fn push_size_limit_reuse_test() {
+        let opts = ParseOptions {
+            max_total_bytes: 10,
+            ..Default::default()
+        };
+        // 限制按单个数据计算, 不累计之前的数据
+        let mut p = Parser::with_options(&opts);
+        for _ in 0..10 {
+            assert_eq!(p.push(b"i1e").unwrap(), Some((BData::Number(1), 3)));
+        }
+        assert!(p.push(b"l3:a").unwrap().is_none());
+        let v = p.push(b"bc1:de").unwrap();
+        assert_eq!(v, Some((bdata!(["abc", "d"]), 6)));
+    }
+
+    #[test]
+    fn push_size_limit_test() {
+        let opts = ParseOptions {
+            max_total_bytes: 64,
+            ..Default::default()
+        };
+        // 声明的长度超出限制时立即返回, 不等待字符串的内容
+        let mut p = Parser::with_options(&opts);
+        assert!(p.push(b"l1:a").unwrap().is_none());
+        assert!(matches!(
+            p.push(b"100000:"),
+            Err(ParseErr::SizeLimitExceeded {
+                offset: 4,
+                limit: 64
+            })
+        ));
+
+        // 同一个解析器依次解析多个数据, 每个数据分别计算, 错误位置仍然相对于整个输入流
+        let mut p = Parser::with_options(&opts);
+        let value = [&b"61:"[..], &[b'a'; 61][..]].concat();
+        for _ in 0..3 {
+            let (data, consumed) = p.push(&value).unwrap().unwrap();
+            assert_eq!((data, consumed), (BData::from(&[b'a'; 61][..]), 64));
+        }
+        assert!(matches!(
+            p.push(b"l62:"),
+            Err(ParseErr::SizeLimitExceeded {
+                offset: 193,
+                limit: 64
+            })
+        ));
+        assert_eq!(p.push(b"i1e").unwrap(), Some((BData::Number(1), 3)));
+    }
+}
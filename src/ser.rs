@@ -0,0 +1,620 @@
+use crate::map::Map;
+use crate::{BData, EncodeError};
+use serde::ser::{self, Serialize, SerializeMap};
+use std::convert::TryInto;
+use std::fmt;
+
+impl ser::Error for EncodeError {
+    fn custom<T: fmt::Display>(msg: T) -> EncodeError {
+        EncodeError::Custom(msg.to_string())
+    }
+}
+
+/// 将 `value` 编码为 bencode
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    crate::stringify(&to_bdata(value)?)
+}
+
+/// 将 `value` 转换为 `BData`.
+///
+/// 结构体与 map 转换为字典, 键按原始字节排序; 字节串与字符串转换为字符串;
+/// 序列与元组转换为列表; `bool` 转换为 `i0e` 或 `i1e`; 超出 `i64` 范围的整数转换为 `BigNumber`.
+/// 结构体与 map 中值为 `None` 的项会被省略, 其他位置的 `None` 以及浮点数会返回错误.
+///
+/// serde 默认将 `Vec<u8>` 作为序列处理, 需要编码为字符串时使用 `serde_bytes` 或 `serialize_with`.
+pub fn to_bdata<T: Serialize + ?Sized>(value: &T) -> Result<BData, EncodeError> {
+    value
+        .serialize(Serializer)?
+        .ok_or(EncodeError::UnsupportedType("none"))
+}
+
+/// 字符串是合法的 UTF-8 时作为字符串输出, 否则作为字节输出.
+///
+/// JSON 等文本格式无法表示字节, 此时不是 UTF-8 的字符串输出为 `{"$hex": "<十六进制>"}`,
+/// 由 `BData` 的 `Deserialize` 还原. 为了与之区分, 唯一的键为 `$hex` 的字典在文本格式中
+/// 输出为 `{"$hex": {"$dict": <值>}}`. 字典的键同样优先作为字符串输出, 文本格式中的键必须是 UTF-8.
+/// `BigNumber` 输出为 128 位整数, 超出范围时返回错误.
+impl Serialize for BData {
+    fn serialize<S: ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BData::BString(v) => match std::str::from_utf8(v) {
+                Ok(v) => s.serialize_str(v),
+                Err(_) if s.is_human_readable() => {
+                    let mut map = s.serialize_map(Some(1))?;
+                    map.serialize_entry(HEX_KEY, &hex(v))?;
+                    map.end()
+                }
+                Err(_) => s.serialize_bytes(v),
+            },
+            BData::Number(n) => s.serialize_i64(*n),
+            BData::BigNumber(n) => {
+                let literal = std::str::from_utf8(n).unwrap_or_default();
+                if let Ok(n) = literal.parse::<i128>() {
+                    s.serialize_i128(n)
+                } else if let Ok(n) = literal.parse::<u128>() {
+                    s.serialize_u128(n)
+                } else {
+                    Err(ser::Error::custom("integer is out of range"))
+                }
+            }
+            BData::List(list) => s.collect_seq(list),
+            BData::Dict(dict) => {
+                // 唯一的键为 `$hex` 时转义, 避免被还原为字节
+                if s.is_human_readable() && dict.len() == 1 {
+                    if let Some(v) = dict.get(HEX_KEY.as_bytes()) {
+                        let mut map = s.serialize_map(Some(1))?;
+                        map.serialize_entry(HEX_KEY, &Escaped(v))?;
+                        return map.end();
+                    }
+                }
+                let mut map = s.serialize_map(Some(dict.len()))?;
+                for (k, v) in dict {
+                    match std::str::from_utf8(k) {
+                        Ok(k) => map.serialize_entry(k, v)?,
+                        Err(_) => map.serialize_entry(&Bytes(k), v)?,
+                    }
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// 文本格式中表示字节的键
+pub(crate) const HEX_KEY: &str = "$hex";
+/// 文本格式中转义唯一的键 `$hex` 时使用的键
+pub(crate) const DICT_KEY: &str = "$dict";
+
+fn hex(v: &[u8]) -> String {
+    v.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 唯一的键为 `$hex` 的字典中的值, 输出为 `{"$dict": <值>}`
+struct Escaped<'a>(&'a BData);
+
+impl Serialize for Escaped<'_> {
+    fn serialize<S: ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(1))?;
+        map.serialize_entry(DICT_KEY, self.0)?;
+        map.end()
+    }
+}
+
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(self.0)
+    }
+}
+
+/// 转换得到 `None` 表示该值为空, 由所在的容器决定省略或报错
+struct Serializer;
+
+fn value<T: Serialize + ?Sized>(value: &T) -> Result<Option<BData>, EncodeError> {
+    value.serialize(Serializer)
+}
+
+/// 列表中的元素不能为空
+fn element<T: Serialize + ?Sized>(v: &T) -> Result<BData, EncodeError> {
+    value(v)?.ok_or(EncodeError::UnsupportedType("none in a list"))
+}
+
+fn key<T: Serialize + ?Sized>(key: &T) -> Result<Vec<u8>, EncodeError> {
+    value(key)?
+        .and_then(|k| k.into_bytes().ok())
+        .ok_or(EncodeError::InvalidKey)
+}
+
+/// 整数在 `i64` 范围内时为 `Number`, 否则为 `BigNumber`
+fn integer<T: TryInto<i64> + ToString + Copy>(n: T) -> Option<BData> {
+    Some(match n.try_into() {
+        Ok(n) => BData::Number(n),
+        Err(_) => BData::BigNumber(n.to_string().into_bytes()),
+    })
+}
+
+/// 带有名称的枚举成员转换为只有一个键值对的字典
+fn variant(name: &str, value: BData) -> Option<BData> {
+    let mut dict = Map::new();
+    dict.insert(name.as_bytes().to_vec(), value);
+    Some(BData::Dict(dict))
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = DictSerializer;
+    type SerializeStruct = DictSerializer;
+    type SerializeStructVariant = DictSerializer;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Option<BData>, EncodeError> {
+        Ok(Some(BData::Number(v as i64)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Option<BData>, EncodeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Option<BData>, EncodeError> {
+        Err(EncodeError::UnsupportedType("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Option<BData>, EncodeError> {
+        Err(EncodeError::UnsupportedType("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Option<BData>, EncodeError> {
+        Ok(Some(BData::from(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Option<BData>, EncodeError> {
+        Ok(Some(BData::from(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Option<BData>, EncodeError> {
+        Ok(Some(BData::from(v)))
+    }
+
+    fn serialize_none(self) -> Result<Option<BData>, EncodeError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, v: &T) -> Result<Option<BData>, EncodeError> {
+        value(v)
+    }
+
+    fn serialize_unit(self) -> Result<Option<BData>, EncodeError> {
+        Err(EncodeError::UnsupportedType("unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<BData>, EncodeError> {
+        Err(EncodeError::UnsupportedType("unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Option<BData>, EncodeError> {
+        Ok(Some(BData::from(variant)))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        v: &T,
+    ) -> Result<Option<BData>, EncodeError> {
+        value(v)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        v: &T,
+    ) -> Result<Option<BData>, EncodeError> {
+        let v = value(v)?.ok_or(EncodeError::UnsupportedType("none in a variant"))?;
+        Ok(variant(name, v))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, EncodeError> {
+        Ok(SeqSerializer {
+            list: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, EncodeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, EncodeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, EncodeError> {
+        Ok(SeqSerializer {
+            list: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<DictSerializer, EncodeError> {
+        Ok(DictSerializer {
+            dict: Map::new(),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<DictSerializer, EncodeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<DictSerializer, EncodeError> {
+        Ok(DictSerializer {
+            dict: Map::new(),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+/// 列表, 以及枚举成员的名称
+struct SeqSerializer {
+    list: Vec<BData>,
+    variant: Option<&'static str>,
+}
+
+impl SeqSerializer {
+    fn finish(self) -> Result<Option<BData>, EncodeError> {
+        let list = BData::List(self.list);
+        Ok(match self.variant {
+            Some(name) => variant(name, list),
+            None => Some(list),
+        })
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), EncodeError> {
+        self.list.push(element(v)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Option<BData>, EncodeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), EncodeError> {
+        ser::SerializeSeq::serialize_element(self, v)
+    }
+
+    fn end(self) -> Result<Option<BData>, EncodeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), EncodeError> {
+        ser::SerializeSeq::serialize_element(self, v)
+    }
+
+    fn end(self) -> Result<Option<BData>, EncodeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), EncodeError> {
+        ser::SerializeSeq::serialize_element(self, v)
+    }
+
+    fn end(self) -> Result<Option<BData>, EncodeError> {
+        self.finish()
+    }
+}
+
+/// 字典, 已转换但尚未对应值的键, 以及枚举成员的名称
+struct DictSerializer {
+    dict: Map<Vec<u8>, BData>,
+    key: Option<Vec<u8>>,
+    variant: Option<&'static str>,
+}
+
+impl DictSerializer {
+    /// 值为空的项被省略
+    fn insert<T: Serialize + ?Sized>(&mut self, key: Vec<u8>, v: &T) -> Result<(), EncodeError> {
+        if let Some(v) = value(v)? {
+            self.dict.insert(key, v);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<BData>, EncodeError> {
+        let dict = BData::Dict(self.dict);
+        Ok(match self.variant {
+            Some(name) => variant(name, dict),
+            None => Some(dict),
+        })
+    }
+}
+
+impl ser::SerializeMap for DictSerializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, k: &T) -> Result<(), EncodeError> {
+        self.key = Some(key(k)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), EncodeError> {
+        let k = self
+            .key
+            .take()
+            .ok_or_else(|| EncodeError::Custom("value without a key".to_string()))?;
+        self.insert(k, v)
+    }
+
+    fn end(self) -> Result<Option<BData>, EncodeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for DictSerializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        v: &T,
+    ) -> Result<(), EncodeError> {
+        self.insert(name.as_bytes().to_vec(), v)
+    }
+
+    fn end(self) -> Result<Option<BData>, EncodeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for DictSerializer {
+    type Ok = Option<BData>;
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        v: &T,
+    ) -> Result<(), EncodeError> {
+        self.insert(name.as_bytes().to_vec(), v)
+    }
+
+    fn end(self) -> Result<Option<BData>, EncodeError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::EncodeError;
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Info {
+        // 字段的声明顺序与键的顺序不同
+        #[serde(serialize_with = "as_bytes")]
+        pieces: Vec<u8>,
+        name: String,
+        #[serde(rename = "piece length")]
+        piece_length: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        private: Option<bool>,
+        md5sum: Option<String>,
+    }
+
+    fn as_bytes<S: serde::Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(v)
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Torrent {
+        info: Info,
+        announce: String,
+        #[serde(rename = "creation date")]
+        creation_date: u64,
+        tags: Vec<(String, i8)>,
+    }
+
+    #[test]
+    fn to_bytes_test() {
+        let torrent = Torrent {
+            info: Info {
+                pieces: vec![0, 255, 10],
+                name: "a.txt".to_string(),
+                piece_length: 16384,
+                private: None,
+                md5sum: Some("x".to_string()),
+            },
+            announce: "http://t".to_string(),
+            creation_date: 1_600_000_000,
+            tags: vec![("a".to_string(), -1)],
+        };
+        let encoded = crate::to_bytes(&torrent).unwrap();
+        let expected = bdata!({
+            "announce" => "http://t",
+            "creation date" => 1_600_000_000,
+            "info" => {
+                "md5sum" => "x",
+                "name" => "a.txt",
+                "piece length" => 16384,
+                "pieces" => &b"\x00\xff\n"[..],
+            },
+            "tags" => [["a", -1]],
+        });
+        assert_eq!(crate::parse(&encoded).unwrap(), expected);
+        assert_eq!(crate::to_bdata(&torrent).unwrap(), expected);
+
+        // 没有指定时 `Vec<u8>` 按序列编码
+        let list = crate::to_bdata(&torrent.info.pieces).unwrap();
+        assert_eq!(list, bdata!([0, 255, 10]));
+
+        let decoded: Torrent = crate::from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, torrent);
+    }
+
+    #[test]
+    fn to_bdata_test() {
+        struct Bytes<'a>(&'a [u8]);
+        impl Serialize for Bytes<'_> {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_bytes(self.0)
+            }
+        }
+        assert_eq!(
+            crate::to_bdata(&Bytes(b"\xff")).unwrap(),
+            bdata!(&b"\xff"[..])
+        );
+        assert_eq!(crate::to_bytes("abc").unwrap(), b"3:abc");
+        assert_eq!(crate::to_bytes(&true).unwrap(), b"i1e");
+        assert_eq!(
+            crate::to_bytes(&u64::MAX).unwrap(),
+            b"i18446744073709551615e"
+        );
+        assert_eq!(crate::to_bytes(&i128::MIN).unwrap().len(), 42);
+
+        let mut map = HashMap::new();
+        map.insert("b", Some(1));
+        map.insert("a", None);
+        map.insert("c", Some(2));
+        assert_eq!(crate::to_bytes(&map).unwrap(), b"d1:bi1e1:ci2ee");
+    }
+
+    #[test]
+    fn enum_test() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        enum E {
+            Unit,
+            Newtype(i32),
+            Tuple(i32, String),
+            Struct { x: i8 },
+        }
+        let v = vec![
+            E::Unit,
+            E::Newtype(1),
+            E::Tuple(2, "a".to_string()),
+            E::Struct { x: -3 },
+        ];
+        let encoded = crate::to_bytes(&v).unwrap();
+        assert_eq!(
+            encoded,
+            &b"l4:Unitd7:Newtypei1eed5:Tupleli2e1:aeed6:Structd1:xi-3eeee"[..]
+        );
+        assert_eq!(crate::from_bytes::<Vec<E>>(&encoded).unwrap(), v);
+    }
+
+    #[test]
+    fn to_bytes_err_test() {
+        assert_eq!(
+            crate::to_bytes(&1.5f64),
+            Err(EncodeError::UnsupportedType("f64"))
+        );
+        assert_eq!(
+            crate::to_bytes(&None::<i64>),
+            Err(EncodeError::UnsupportedType("none"))
+        );
+        assert!(crate::to_bytes(&vec![Some(1), None]).is_err());
+        assert!(crate::to_bytes(&()).is_err());
+
+        let mut map = BTreeMap::new();
+        map.insert(1, "a");
+        assert_eq!(crate::to_bytes(&map), Err(EncodeError::InvalidKey));
+        assert_eq!(
+            crate::to_bytes(&1.5f32).unwrap_err().to_string(),
+            "f32 cannot be represented in bencode"
+        );
+    }
+}
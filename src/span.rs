@@ -0,0 +1,180 @@
+use crate::{DuplicateKeyPolicy, ParseOptions, Token};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// 数据以及其中各个子数据在输入中的位置, 结构与对应的 `BData` 相同.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanTree {
+    /// 完整编码 (包括类型前缀以及结尾的 `e`) 在输入中的范围
+    pub span: Range<usize>,
+    pub children: SpanChildren,
+}
+
+/// 列表的元素或字典的值的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpanChildren {
+    /// 字符串与整数没有子数据
+    Leaf,
+    List(Vec<SpanTree>),
+    Dict(BTreeMap<Vec<u8>, SpanTree>),
+}
+
+impl SpanTree {
+    /// 列表中第 `index` 个元素的位置
+    pub fn index(&self, index: usize) -> Option<&SpanTree> {
+        match &self.children {
+            SpanChildren::List(list) => list.get(index),
+            _ => None,
+        }
+    }
+
+    /// 字典中键为 `key` 的值的位置
+    pub fn get(&self, key: &[u8]) -> Option<&SpanTree> {
+        match &self.children {
+            SpanChildren::Dict(dict) => dict.get(key),
+            _ => None,
+        }
+    }
+
+    /// 从解析时的输入中取出该数据的原始编码
+    pub fn slice<'a>(&self, src: &'a [u8]) -> &'a [u8] {
+        &src[self.span.clone()]
+    }
+}
+
+/// 尚未结束的容器以及其起始位置
+enum Frame {
+    List(usize, Vec<SpanTree>),
+    /// 字典以及已读取但还没有对应值的键
+    Dict(usize, BTreeMap<Vec<u8>, SpanTree>, Option<Vec<u8>>),
+}
+
+/// 根据 token 的位置构建 `SpanTree`.
+///
+/// 只记录位置, 不检查数据格式, 需要与 `Builder` 一同使用.
+pub(crate) struct SpanBuilder {
+    stack: Vec<Frame>,
+    duplicate_keys: DuplicateKeyPolicy,
+}
+
+impl SpanBuilder {
+    pub(crate) fn with_options(opts: &ParseOptions) -> SpanBuilder {
+        SpanBuilder {
+            stack: Vec::new(),
+            duplicate_keys: opts.duplicate_keys,
+        }
+    }
+
+    /// 读入位于 `start..end` 的 token, 最外层的数据完成时返回其位置
+    pub(crate) fn push(&mut self, start: usize, end: usize, token: Token) -> Option<SpanTree> {
+        let (begin, children) = match token {
+            Token::ListStart => {
+                self.stack.push(Frame::List(start, Vec::new()));
+                return None;
+            }
+            Token::DictStart => {
+                self.stack.push(Frame::Dict(start, BTreeMap::new(), None));
+                return None;
+            }
+            Token::ListEnd | Token::DictEnd => match self.stack.pop()? {
+                Frame::List(s, list) => (s, SpanChildren::List(list)),
+                Frame::Dict(s, dict, _) => (s, SpanChildren::Dict(dict)),
+            },
+            Token::Bytes(_) | Token::Int(_) | Token::BigInt(_) => (start, SpanChildren::Leaf),
+        };
+        let tree = SpanTree {
+            span: begin..end,
+            children,
+        };
+
+        match self.stack.last_mut() {
+            None => return Some(tree),
+            Some(Frame::List(_, list)) => list.push(tree),
+            Some(Frame::Dict(_, dict, key)) => match key.take() {
+                Some(k) => match self.duplicate_keys {
+                    DuplicateKeyPolicy::KeepFirst => {
+                        dict.entry(k).or_insert(tree);
+                    }
+                    _ => {
+                        dict.insert(k, tree);
+                    }
+                },
+                None => {
+                    if let Token::Bytes(k) = token {
+                        *key = Some(k.to_vec());
+                    }
+                }
+            },
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SpanChildren;
+    use crate::{DuplicateKeyPolicy, ParseErr, ParseOptions};
+
+    #[test]
+    fn parse_with_spans_test() {
+        let src = b"d8:announce3:url4:infod6:lengthi1024e4:name5:a.txte4:listl1:xi-7eee";
+        let (data, spans) = crate::parse_with_spans(src).unwrap();
+        assert_eq!(spans.span, 0..src.len());
+
+        let info = spans.get(b"info").unwrap();
+        let raw = info.slice(src);
+        assert_eq!(raw, &b"d6:lengthi1024e4:name5:a.txte"[..]);
+        let info_data = &data.as_dict().unwrap()[&b"info"[..]];
+        assert_eq!(&crate::parse(raw).unwrap(), info_data);
+
+        let name = info.get(b"name").unwrap();
+        assert_eq!(name.slice(src), b"5:a.txt");
+        assert_eq!(name.children, SpanChildren::Leaf);
+
+        let list = spans.get(b"list").unwrap();
+        assert_eq!(list.slice(src), b"l1:xi-7ee");
+        assert_eq!(list.index(1).unwrap().slice(src), b"i-7e");
+        assert!(list.index(2).is_none());
+        assert!(list.get(b"x").is_none());
+        assert!(info.index(0).is_none());
+    }
+
+    #[test]
+    fn spans_raw_info_test() {
+        // info 的键没有排序, 重新编码会得到不同的字节
+        let src = b"d4:infod4:name1:a6:lengthi1ee8:url-listl0:ee";
+        let (data, spans) = crate::parse_with_spans(src).unwrap();
+        let info = spans.get(b"info").unwrap();
+        assert_eq!(info.span, 7..29);
+        let raw = info.slice(src);
+        assert_eq!(raw, b"d4:name1:a6:lengthi1ee");
+        assert_eq!(raw.as_ptr(), src[7..].as_ptr());
+
+        let info_data = &data.as_dict().unwrap()[&b"info"[..]];
+        assert_ne!(crate::stringify(info_data).unwrap(), raw);
+    }
+
+    #[test]
+    fn parse_with_spans_err_test() {
+        let v = crate::parse_with_spans(b"li1e");
+        assert!(matches!(v, Err(ParseErr::UnexpectedEof { offset: 4, .. })));
+        let v = crate::parse_with_spans(b"lei1e");
+        assert!(matches!(v, Err(ParseErr::TrailingData { offset: 2 })));
+    }
+
+    #[test]
+    fn spans_duplicate_key_test() {
+        let src = b"d1:ai1e1:ai22ee";
+        let mut opts = ParseOptions {
+            duplicate_keys: DuplicateKeyPolicy::KeepFirst,
+            ..Default::default()
+        };
+        let (_, spans) = crate::parse_with_spans_options(src, &opts).unwrap();
+        assert_eq!(spans.get(b"a").unwrap().slice(src), b"i1e");
+
+        opts.duplicate_keys = DuplicateKeyPolicy::KeepLast;
+        let (_, spans) = crate::parse_with_spans_options(src, &opts).unwrap();
+        assert_eq!(spans.get(b"a").unwrap().slice(src), b"i22e");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::BData;
+use alloc::vec;
+
+/// `BData::stats` 的统计结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// 值的个数, 包括自身与所有嵌套的值, 不包括字典的键
+    pub node_count: usize,
+    /// 容器嵌套的最大层数, 与 `ParseOptions::max_depth` 的计算方式相同, 不是容器时为 0
+    pub max_depth: usize,
+    /// 所有字符串与字典的键的字节数之和
+    pub string_bytes: usize,
+    /// 所有列表的元素个数之和
+    pub list_items: usize,
+    /// 所有字典的键值对个数之和
+    pub dict_entries: usize,
+}
+
+impl BData {
+    /// 统计数据的结构, 用于在处理不可信的数据前检查其规模.
+    ///
+    /// 不使用递归, 嵌套很深的数据也不会栈溢出
+    ///
+    /// ```
+    /// use fraux_rs::{bdata, Stats};
+    /// let data = bdata!({"files" => [{"path" => ["a"]}], "name" => "dir"});
+    /// let stats = Stats {
+    ///     node_count: 6,
+    ///     max_depth: 4,
+    ///     string_bytes: 17,
+    ///     list_items: 2,
+    ///     dict_entries: 3,
+    /// };
+    /// assert_eq!(data.stats(), stats);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        let mut stack = vec![(self, 0)];
+        while let Some((data, depth)) = stack.pop() {
+            stats.node_count += 1;
+            match data {
+                BData::BString(s) => stats.string_bytes += s.len(),
+                BData::Number(_) | BData::BigNumber(_) => {}
+                BData::List(list) => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    stats.list_items += list.len();
+                    stack.extend(list.iter().map(|v| (v, depth + 1)));
+                }
+                BData::Dict(dict) => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    stats.dict_entries += dict.len();
+                    for (k, v) in dict.iter() {
+                        stats.string_bytes += k.len();
+                        stack.push((v, depth + 1));
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    /// 容器嵌套的最大层数, 与 `stats().max_depth` 相同但不统计其他数据
+    pub fn max_depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 0)];
+        while let Some((data, depth)) = stack.pop() {
+            match data {
+                BData::List(list) => stack.extend(list.iter().map(|v| (v, depth + 1))),
+                BData::Dict(dict) => stack.extend(dict.values().map(|v| (v, depth + 1))),
+                _ => continue,
+            }
+            max = max.max(depth + 1);
+        }
+        max
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Stats;
+    use crate::BData;
+
+    #[test]
+    fn stats_test() {
+        let empty = bdata!({});
+        let stats = Stats {
+            node_count: 1,
+            max_depth: 1,
+            ..Stats::default()
+        };
+        assert_eq!(empty.stats(), stats);
+        assert_eq!(empty.max_depth(), 1);
+
+        let leaf = bdata!("abc");
+        let stats = Stats {
+            node_count: 1,
+            string_bytes: 3,
+            ..Stats::default()
+        };
+        assert_eq!(leaf.stats(), stats);
+        assert_eq!(bdata!(1).max_depth(), 0);
+
+        let wide: BData = (0..1000).map(BData::from).collect();
+        let stats = Stats {
+            node_count: 1001,
+            max_depth: 1,
+            list_items: 1000,
+            ..Stats::default()
+        };
+        assert_eq!(wide.stats(), stats);
+
+        let data = bdata!({
+            "a" => [1, "xy", {}],
+            "bc" => {"d" => [[]]},
+            "e" => BData::BigNumber(b"18446744073709551616".to_vec()),
+        });
+        let stats = Stats {
+            node_count: 9,
+            max_depth: 4,
+            string_bytes: 7,
+            list_items: 4,
+            dict_entries: 4,
+        };
+        assert_eq!(data.stats(), stats);
+        assert_eq!(data.max_depth(), 4);
+    }
+
+    #[test]
+    fn stats_deep_test() {
+        // 递归实现会栈溢出的深度
+        let mut data = bdata!({"x" => "a"});
+        for _ in 0..100_000 {
+            data = BData::List(vec![data]);
+        }
+        let stats = data.stats();
+        assert_eq!(stats.max_depth, 100_001);
+        assert_eq!(stats.node_count, 100_002);
+        assert_eq!(stats.string_bytes, 2);
+        assert_eq!(stats.list_items, 100_000);
+        assert_eq!(stats.dict_entries, 1);
+        assert_eq!(data.max_depth(), 100_001);
+    }
+}
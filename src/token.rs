@@ -0,0 +1,502 @@
+use crate::{ParseErr, ParseOptions};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::num::{IntErrorKind, ParseIntError};
+
+/// 解析过程中产生的事件
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum Token<'a> {
+    DictStart,
+    DictEnd,
+    ListStart,
+    ListEnd,
+    Bytes(&'a [u8]),
+    Int(i64),
+    /// 超出 `i64` 范围的整数, 内容为原始的数字 (包括符号).
+    /// 仅在 `ParseOptions::big_numbers` 开启时产生
+    BigInt(&'a [u8]),
+}
+
+/// 默认的最大嵌套层数
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// 尚未结束的容器, `items` 为已读取的元素个数
+enum Frame {
+    List {
+        items: usize,
+    },
+    /// `key` 为 true 时下一个元素应当是字典的键
+    Dict {
+        key: bool,
+        items: usize,
+    },
+}
+
+/// 词法分析器, 负责检查数据格式并产生 token.
+///
+/// 数据不完整时不会读取残缺的 token, 调用方补充数据之后可以从同一位置继续.
+pub(crate) struct Lexer {
+    stack: Vec<Frame>,
+    opts: ParseOptions,
+    /// 输入切片的第一个字节在整个数据流中的位置, 用于计算错误位置
+    pub(crate) base: usize,
+    /// 当前数据的起始位置, 总长度限制从这里开始计算
+    value_start: usize,
+}
+
+impl Lexer {
+    pub(crate) fn new() -> Lexer {
+        Lexer::with_options(&ParseOptions::default())
+    }
+
+    pub(crate) fn with_options(opts: &ParseOptions) -> Lexer {
+        Lexer {
+            stack: Vec::new(),
+            opts: opts.clone(),
+            base: 0,
+            value_start: 0,
+        }
+    }
+
+    /// 清除解析状态, 保留选项
+    pub(crate) fn reset(&mut self) {
+        self.stack.clear();
+        self.base = 0;
+        self.value_start = 0;
+    }
+
+    /// 当前尚未结束的容器层数
+    pub(crate) fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// 从 `src[pos..]` 读取下一个 token, 返回 token 以及其后的位置.
+    ///
+    /// 数据不完整时返回 `Ok(None)`, 此时状态不会改变.
+    pub(crate) fn next_token<'a>(
+        &mut self,
+        src: &'a [u8],
+        pos: usize,
+    ) -> Result<Option<(Token<'a>, usize)>, ParseErr> {
+        let c = match src.get(pos) {
+            Some(c) => *c,
+            None => return Ok(None),
+        };
+        if self.stack.is_empty() {
+            self.value_start = self.base + pos;
+        }
+
+        // 字典中需要键的位置只能是字符串或结束符
+        let expect_key = matches!(self.stack.last(), Some(Frame::Dict { key: true, .. }));
+        if expect_key && !matches!(c, b'0'..=b'9' | b'e') {
+            return Err(ParseErr::NonStringDictKey {
+                offset: self.base + pos,
+            });
+        }
+
+        // 新元素开始之前检查容器的元素个数
+        let items = match self.stack.last() {
+            Some(Frame::List { items }) => Some(*items),
+            Some(Frame::Dict { key: true, items }) => Some(*items),
+            _ => None,
+        };
+        match items {
+            Some(items) if c != b'e' && items >= self.opts.max_items => {
+                return Err(ParseErr::TooManyItems {
+                    offset: self.base + pos,
+                    limit: self.opts.max_items,
+                });
+            }
+            _ => {}
+        }
+
+        let lexed = match c {
+            b'0'..=b'9' => self.lex_bytes(src, pos)?,
+            b'i' => self.lex_number(src, pos)?,
+            b'l' | b'd' if self.stack.len() >= self.opts.max_depth => {
+                return Err(ParseErr::DepthLimitExceeded {
+                    offset: self.base + pos,
+                })
+            }
+            b'l' => Some((Token::ListStart, pos + 1)),
+            b'd' => Some((Token::DictStart, pos + 1)),
+            b'e' => match self.stack.last() {
+                Some(Frame::List { .. }) => Some((Token::ListEnd, pos + 1)),
+                Some(Frame::Dict { key: true, .. }) => Some((Token::DictEnd, pos + 1)),
+                _ => return Err(self.unexpected_byte(c, self.expected(), pos)),
+            },
+            _ => return Err(self.unexpected_byte(c, self.expected(), pos)),
+        };
+
+        let (token, end) = match lexed {
+            Some(v) => v,
+            None => {
+                // 残缺的 token 也不能超出总长度限制
+                self.check_size(pos, src.len())?;
+                return Ok(None);
+            }
+        };
+        self.check_size(pos, end)?;
+
+        match token {
+            Token::ListStart => self.stack.push(Frame::List { items: 0 }),
+            Token::DictStart => self.stack.push(Frame::Dict {
+                key: true,
+                items: 0,
+            }),
+            Token::ListEnd | Token::DictEnd => {
+                self.stack.pop();
+                self.complete();
+            }
+            Token::Bytes(_) | Token::Int(_) | Token::BigInt(_) => self.complete(),
+        }
+        Ok(Some((token, end)))
+    }
+
+    /// 一个元素结束, 字典在键和值之间切换
+    fn complete(&mut self) {
+        match self.stack.last_mut() {
+            Some(Frame::List { items }) => *items += 1,
+            Some(Frame::Dict { key, items }) => {
+                if *key {
+                    *items += 1;
+                }
+                *key = !*key;
+            }
+            None => {}
+        }
+    }
+
+    /// 检查从 `pos` 开始的 token 读取到 `end` 位置时当前数据是否超出总长度限制
+    fn check_size(&self, pos: usize, end: usize) -> Result<(), ParseErr> {
+        if self.base.saturating_add(end) - self.value_start > self.opts.max_total_bytes {
+            return Err(ParseErr::SizeLimitExceeded {
+                offset: self.base + pos,
+                limit: self.opts.max_total_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// 在 token 的边界上期望读到的内容
+    fn expected(&self) -> &'static str {
+        match self.stack.last() {
+            None => "value",
+            Some(Frame::List { .. }) => "value or 'e'",
+            Some(Frame::Dict { key: true, .. }) => "key or 'e'",
+            Some(Frame::Dict { key: false, .. }) => "value",
+        }
+    }
+
+    /// 数据在 `src` 的结尾处中断时期望读到的内容, `pos` 为残缺的 token 的位置
+    pub(crate) fn expected_at_end(&self, src: &[u8], pos: usize) -> &'static str {
+        match src.get(pos) {
+            None => self.expected(),
+            Some(b'i') => "integer",
+            Some(_) => "string",
+        }
+    }
+
+    fn unexpected_byte(&self, found: u8, expected: &'static str, pos: usize) -> ParseErr {
+        ParseErr::UnexpectedByte {
+            found,
+            expected,
+            offset: self.base + pos,
+        }
+    }
+
+    fn invalid_integer(&self, literal: &[u8], pos: usize) -> ParseErr {
+        ParseErr::InvalidInteger {
+            literal: String::from_utf8_lossy(literal).into_owned(),
+            offset: self.base + pos,
+        }
+    }
+
+    fn lex_number<'a>(
+        &self,
+        src: &'a [u8],
+        pos: usize,
+    ) -> Result<Option<(Token<'a>, usize)>, ParseErr> {
+        let start = pos + 1;
+        let mut symb = false;
+        for (i, c) in src[start..].iter().enumerate() {
+            match c {
+                b'0'..=b'9' => {}
+                b'-' if !symb => symb = true,
+                b'e' => {
+                    let num = &src[start..start + i];
+                    if self.opts.strict_integers && has_leading_zero(num) {
+                        return Err(self.invalid_integer(num, pos));
+                    }
+                    let token = match decode_number(num) {
+                        Some(Ok(n)) => Token::Int(n),
+                        Some(Err(e)) if is_overflow(&e) => {
+                            if !self.opts.big_numbers {
+                                return Err(ParseErr::IntegerOverflow {
+                                    literal: String::from_utf8_lossy(num).into_owned(),
+                                    offset: self.base + pos,
+                                });
+                            }
+                            Token::BigInt(num)
+                        }
+                        _ => return Err(self.invalid_integer(num, pos)),
+                    };
+                    return Ok(Some((token, start + i + 1)));
+                }
+                c => return Err(self.unexpected_byte(*c, "digit or 'e'", start + i)),
+            }
+        }
+        Ok(None)
+    }
+
+    fn lex_bytes<'a>(
+        &self,
+        src: &'a [u8],
+        pos: usize,
+    ) -> Result<Option<(Token<'a>, usize)>, ParseErr> {
+        let mut len: usize = 0;
+        let mut i = pos;
+        loop {
+            match src.get(i) {
+                Some(c @ b'0'..=b'9') => {
+                    len = len
+                        .checked_mul(10)
+                        .and_then(|len| len.checked_add((c - b'0') as usize))
+                        .ok_or_else(|| ParseErr::IntegerOverflow {
+                            literal: String::from_utf8_lossy(&src[pos..=i]).into_owned(),
+                            offset: self.base + pos,
+                        })?;
+                }
+                Some(b':') => break,
+                Some(c) => return Err(self.unexpected_byte(*c, "digit or ':'", i)),
+                None => return Ok(None),
+            }
+            i += 1;
+        }
+
+        if self.opts.strict_integers && has_leading_zero(&src[pos..i]) {
+            return Err(self.invalid_integer(&src[pos..i], pos));
+        }
+
+        // 在读取内容之前检查声明的长度
+        if len > self.opts.max_string_len {
+            return Err(ParseErr::StringTooLong {
+                offset: self.base + pos,
+                declared: len,
+                limit: self.opts.max_string_len,
+            });
+        }
+        let start = i + 1;
+        self.check_size(pos, start.saturating_add(len))?;
+        if src.len() - start < len {
+            return Ok(None);
+        }
+        Ok(Some((Token::Bytes(&src[start..start + len]), start + len)))
+    }
+}
+
+/// 将 `i` 与 `e` 之间的整数体转换为整数, 整数体本身格式错误时返回 `None`
+fn decode_number(num: &[u8]) -> Option<Result<i64, ParseIntError>> {
+    // 整数体不能为空或只有符号, 也不允许出现 -0
+    let digits = match num.first() {
+        Some(b'-') => &num[1..],
+        _ => num,
+    };
+    if digits.is_empty() || (num[0] == b'-' && digits.iter().all(|c| *c == b'0')) {
+        return None;
+    }
+
+    // 整数体只包含数字与 `-`, 一定是合法的 UTF-8
+    let s = core::str::from_utf8(num).ok()?;
+    Some(s.parse::<i64>())
+}
+
+fn is_overflow(e: &ParseIntError) -> bool {
+    matches!(
+        e.kind(),
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+    )
+}
+
+/// 整数或长度是否带有多余的前导零, 例如 `03` 与 `-03`
+fn has_leading_zero(num: &[u8]) -> bool {
+    let digits = match num.first() {
+        Some(b'-') => &num[1..],
+        _ => num,
+    };
+    digits.len() > 1 && digits[0] == b'0'
+}
+
+/// 在字节切片上逐个产生 token, 不构建数据树.
+///
+/// 每个 token 附带其在输入中的起始位置. 只读取一个完整的数据,
+/// 之后的字节不会被读取, 可以通过 `offset` 获得数据结尾的位置.
+/// 数据格式错误时产生一个 `Err`, 之后不再产生 token.
+pub struct Tokenizer<'a> {
+    src: &'a [u8],
+    pos: usize,
+    lexer: Lexer,
+    done: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(src: &'a [u8]) -> Tokenizer<'a> {
+        Tokenizer::with_lexer(src, 0, Lexer::new())
+    }
+
+    /// 从 `src[pos..]` 开始读取, token 的位置仍然相对于整个 `src`
+    pub(crate) fn with_lexer(src: &'a [u8], pos: usize, lexer: Lexer) -> Tokenizer<'a> {
+        Tokenizer {
+            src,
+            pos,
+            lexer,
+            done: false,
+        }
+    }
+
+    /// 当前的读取位置, 数据读取完成后即为数据结尾的位置
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<(usize, Token<'a>), ParseErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.lexer.next_token(self.src, self.pos) {
+            Ok(Some((token, end))) => {
+                let offset = self.pos;
+                self.pos = end;
+                self.done = self.lexer.depth() == 0;
+                Some(Ok((offset, token)))
+            }
+            Ok(None) => {
+                self.done = true;
+                Some(Err(ParseErr::UnexpectedEof {
+                    expected: self.lexer.expected_at_end(self.src, self.pos),
+                    offset: self.src.len(),
+                }))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Token, Tokenizer};
+    use crate::{BData, Map, ParseErr};
+
+    /// 不依赖解析器, 直接由 token 还原数据
+    fn rebuild(tokens: &[Token], pos: &mut usize) -> BData {
+        let token = tokens[*pos];
+        *pos += 1;
+        match token {
+            Token::Bytes(b) => BData::BString(b.to_vec()),
+            Token::Int(n) => BData::Number(n),
+            Token::BigInt(n) => BData::BigNumber(n.to_vec()),
+            Token::ListStart => {
+                let mut list = Vec::new();
+                while tokens[*pos] != Token::ListEnd {
+                    list.push(rebuild(tokens, pos));
+                }
+                *pos += 1;
+                BData::List(list)
+            }
+            Token::DictStart => {
+                let mut map = Map::new();
+                while let Token::Bytes(k) = tokens[*pos] {
+                    *pos += 1;
+                    let v = rebuild(tokens, pos);
+                    map.insert(k.to_vec(), v);
+                }
+                assert_eq!(tokens[*pos], Token::DictEnd);
+                *pos += 1;
+                BData::Dict(map)
+            }
+            t => panic!("unexpected token {:?}", t),
+        }
+    }
+
+    #[test]
+    fn tokenizer_rebuild_test() {
+        let sources = [
+            "3:abc",
+            "i-32e",
+            "le",
+            "de",
+            "l3:abci32el2:abee",
+            "d2:k13:abc2:k2l3:defi-23eee",
+            "d1:ad1:bl1:cdeee1:bi7ee",
+        ];
+        for s in sources.iter() {
+            let tokens: Vec<Token> = Tokenizer::new(s.as_bytes()).map(|t| t.unwrap().1).collect();
+            let data = rebuild(&tokens, &mut 0);
+            assert_eq!(data, crate::parse_str(s).unwrap());
+        }
+    }
+
+    #[test]
+    fn tokenizer_offset_test() {
+        let mut t = Tokenizer::new(b"l3:abci1ee2:xy");
+        let tokens: Vec<(usize, Token)> = t.by_ref().map(|t| t.unwrap()).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (0, Token::ListStart),
+                (1, Token::Bytes(b"abc")),
+                (6, Token::Int(1)),
+                (9, Token::ListEnd),
+            ]
+        );
+        assert_eq!(t.offset(), 10);
+    }
+
+    #[test]
+    fn tokenizer_err_test() {
+        let mut t = Tokenizer::new(b"l3:abcXe");
+        assert!(matches!(t.next(), Some(Ok((0, Token::ListStart)))));
+        assert!(matches!(t.next(), Some(Ok((1, Token::Bytes(b"abc"))))));
+        assert!(matches!(
+            t.next(),
+            Some(Err(ParseErr::UnexpectedByte {
+                found: b'X',
+                offset: 6,
+                ..
+            }))
+        ));
+        assert!(t.next().is_none());
+
+        let src = format!("{}0:", usize::MAX);
+        let mut t = Tokenizer::new(src.as_bytes());
+        match t.next() {
+            Some(Err(ParseErr::IntegerOverflow { literal, offset: 0 })) => {
+                assert_eq!(literal, src[..src.len() - 1]);
+            }
+            v => panic!("expected IntegerOverflow, got {:?}", v),
+        }
+        let src = format!("{}:", usize::MAX);
+        let mut t = Tokenizer::new(src.as_bytes());
+        assert!(
+            matches!(t.next(), Some(Err(ParseErr::UnexpectedEof { offset: o, .. })) if o == src.len())
+        );
+
+        let mut t = Tokenizer::new(b"l3:ab");
+        assert!(matches!(t.next(), Some(Ok((0, Token::ListStart)))));
+        assert!(matches!(
+            t.next(),
+            Some(Err(ParseErr::UnexpectedEof {
+                expected: "string",
+                offset: 5
+            }))
+        ));
+    }
+}
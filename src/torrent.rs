@@ -0,0 +1,77 @@
+use crate::ParseErr;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+/// torrent 文件中 `info` 字典的原始编码.
+///
+/// 顶层不是字典或没有 `info` 字典时返回 `Ok(None)`
+pub fn info_bytes(src: &[u8]) -> Result<Option<&[u8]>, ParseErr> {
+    let (data, spans) = crate::parse_with_spans(src)?;
+    let is_dict = data
+        .as_dict()
+        .and_then(|dict| dict.get(&b"info"[..]))
+        .and_then(|info| info.as_dict())
+        .is_some();
+    if !is_dict {
+        return Ok(None);
+    }
+    Ok(spans.get(b"info").map(|info| info.slice(src)))
+}
+
+/// BitTorrent v1 的 info hash, 即 `info` 字典原始编码的 SHA-1.
+///
+/// 使用文件中的原始字节计算, 不会因为重新编码 (如键的顺序) 而改变
+pub fn info_hash(src: &[u8]) -> Result<Option<[u8; 20]>, ParseErr> {
+    Ok(info_bytes(src)?.map(|info| Sha1::digest(info).into()))
+}
+
+/// BitTorrent v2 的 info hash, 即 `info` 字典原始编码的 SHA-256
+pub fn info_hash_v2(src: &[u8]) -> Result<Option<[u8; 32]>, ParseErr> {
+    Ok(info_bytes(src)?.map(|info| Sha256::digest(info).into()))
+}
+
+#[cfg(test)]
+mod test {
+    fn hex(v: &[u8]) -> String {
+        v.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn torrent() -> Vec<u8> {
+        let mut src = b"d8:announce23:http://tracker/announce4:infod6:lengthi12345e\
+            4:name8:file.txt12:piece lengthi16384e6:pieces20:"
+            .to_vec();
+        src.extend(0..20u8);
+        src.extend_from_slice(b"ee");
+        src
+    }
+
+    #[test]
+    fn info_hash_test() {
+        let src = torrent();
+        let hash = crate::info_hash(&src).unwrap().unwrap();
+        assert_eq!(hex(&hash), "a13a22cedb4e17039361833d6cfa97681ac90f27");
+        let hash = crate::info_hash_v2(&src).unwrap().unwrap();
+        assert_eq!(
+            hex(&hash),
+            "d300d8f6053c565d99d04cb6cce4802f8e475dd11d1b5eae47566dbbaeb46fa0"
+        );
+    }
+
+    #[test]
+    fn info_hash_raw_bytes_test() {
+        // 键没有排序, 重新编码后的结果与原始字节不同
+        let src = b"d4:infod4:name1:a6:lengthi1eee";
+        let info = crate::info_bytes(src).unwrap().unwrap();
+        assert_eq!(info, b"d4:name1:a6:lengthi1ee");
+        let hash = crate::info_hash(src).unwrap().unwrap();
+        assert_eq!(hex(&hash), "85a3a9249062df75b75ada08228c85924add19df");
+    }
+
+    #[test]
+    fn info_hash_missing_test() {
+        assert_eq!(crate::info_hash(b"d8:announce1:xe").unwrap(), None);
+        assert_eq!(crate::info_hash(b"d4:info1:xe").unwrap(), None);
+        assert_eq!(crate::info_hash(b"l4:infoe").unwrap(), None);
+        assert!(crate::info_hash(b"d4:infod").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{ParseErr, Token, Tokenizer};
+use alloc::vec::Vec;
+
+/// `visit` 在读取数据时调用的回调, 各方法默认不做任何事.
+///
+/// 字典中的键通过 `on_key` 传入, 值与列表元素通过其余的方法传入
+pub trait Visitor {
+    fn on_integer(&mut self, _n: i64) {}
+
+    /// 超出 `i64` 范围的整数, 参见 `BData::BigNumber`
+    fn on_big_integer(&mut self, _n: &[u8]) {}
+
+    fn on_bytes(&mut self, _b: &[u8]) {}
+
+    fn on_list_start(&mut self) {}
+
+    fn on_list_end(&mut self) {}
+
+    fn on_dict_start(&mut self) {}
+
+    fn on_key(&mut self, _key: &[u8]) {}
+
+    fn on_dict_end(&mut self) {}
+}
+
+/// 读取 `src` 中的一个完整数据并依次调用 `visitor` 的回调, 不构建数据树.
+///
+/// 格式检查与 `parse` 相同, 但不检查字典中重复的键.
+/// 出错之前的回调已经被调用, 数据之后有多余字节时返回 `ParseErr::TrailingData`.
+pub fn visit<V: Visitor>(src: &[u8], visitor: &mut V) -> Result<(), ParseErr> {
+    let mut tokens = Tokenizer::new(src);
+    // 尚未结束的容器, 列表为 `None`, 字典为下一个元素是否为键
+    let mut stack: Vec<Option<bool>> = Vec::new();
+    for token in tokens.by_ref() {
+        let (_, token) = token?;
+        match token {
+            Token::Bytes(b) if stack.last() == Some(&Some(true)) => {
+                visitor.on_key(b);
+                stack.last_mut().unwrap().replace(false);
+                continue;
+            }
+            Token::Bytes(b) => visitor.on_bytes(b),
+            Token::Int(n) => visitor.on_integer(n),
+            Token::BigInt(n) => visitor.on_big_integer(n),
+            Token::ListStart => {
+                visitor.on_list_start();
+                stack.push(None);
+                continue;
+            }
+            Token::DictStart => {
+                visitor.on_dict_start();
+                stack.push(Some(true));
+                continue;
+            }
+            Token::ListEnd => {
+                stack.pop();
+                visitor.on_list_end();
+            }
+            Token::DictEnd => {
+                stack.pop();
+                visitor.on_dict_end();
+            }
+        }
+        // 一个值结束, 所在字典的下一个元素为键
+        if let Some(Some(key)) = stack.last_mut() {
+            *key = true;
+        }
+    }
+    if tokens.offset() < src.len() {
+        return Err(ParseErr::TrailingData {
+            offset: tokens.offset(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::Visitor;
+    use crate::ParseErr;
+
+    #[derive(Default)]
+    struct CountBytes {
+        bytes: usize,
+        keys: usize,
+    }
+
+    impl Visitor for CountBytes {
+        fn on_bytes(&mut self, _b: &[u8]) {
+            self.bytes += 1;
+        }
+
+        fn on_key(&mut self, _key: &[u8]) {
+            self.keys += 1;
+        }
+    }
+
+    /// 记录所有回调, 用于检查调用顺序
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl Visitor for Events {
+        fn on_integer(&mut self, n: i64) {
+            self.0.push(format!("i{}", n));
+        }
+
+        fn on_bytes(&mut self, b: &[u8]) {
+            self.0.push(String::from_utf8_lossy(b).into_owned());
+        }
+
+        fn on_list_start(&mut self) {
+            self.0.push("[".to_string());
+        }
+
+        fn on_list_end(&mut self) {
+            self.0.push("]".to_string());
+        }
+
+        fn on_dict_start(&mut self) {
+            self.0.push("{".to_string());
+        }
+
+        fn on_key(&mut self, key: &[u8]) {
+            self.0.push(format!("{}:", String::from_utf8_lossy(key)));
+        }
+
+        fn on_dict_end(&mut self) {
+            self.0.push("}".to_string());
+        }
+    }
+
+    #[test]
+    fn visit_count_test() {
+        let src = b"d8:announce1:t4:infod5:filesld6:lengthi1e4:pathl1:aeee4:name1:xee";
+        let mut v = CountBytes::default();
+        crate::visit(src, &mut v).unwrap();
+        assert_eq!((v.bytes, v.keys), (3, 6));
+    }
+
+    #[test]
+    fn visit_order_test() {
+        let src = b"d1:ad1:bli1e1:cdee1:di2ee1:el0:ee";
+        let mut v = Events::default();
+        crate::visit(src, &mut v).unwrap();
+        let expected = [
+            "{", "a:", "{", "b:", "[", "i1", "c", "{", "}", "]", "d:", "i2", "}", "e:", "[", "",
+            "]", "}",
+        ];
+        assert_eq!(v.0, expected);
+    }
+
+    #[test]
+    fn visit_err_test() {
+        let mut v = Events::default();
+        let e = crate::visit(b"li1ei2", &mut v).unwrap_err();
+        assert!(matches!(e, ParseErr::UnexpectedEof { offset: 6, .. }));
+        // 出错之前的回调已经被调用
+        assert_eq!(v.0, ["[", "i1"]);
+
+        let e = crate::visit(b"i1e0:", &mut v).unwrap_err();
+        assert!(matches!(e, ParseErr::TrailingData { offset: 3 }));
+        let e = crate::visit(b"di1ei2ee", &mut v).unwrap_err();
+        assert!(matches!(e, ParseErr::NonStringDictKey { offset: 1 }));
+        // 不检查重复的键
+        crate::visit(b"d1:ai1e1:ai2ee", &mut v).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{BData, BPathSeg};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+
+impl BData {
+    /// 深度优先依次访问自身与所有嵌套的值, `f` 的参数为从根到该值的路径与该值.
+    ///
+    /// 先访问容器再访问其中的元素, 字典按遍历的顺序访问. `f` 返回 `ControlFlow::Break`
+    /// 时立即停止并返回 `Break`. 不使用递归, 嵌套很深的数据也不会栈溢出
+    ///
+    /// ```
+    /// use fraux_rs::{bdata, BPathSeg};
+    /// use std::ops::ControlFlow;
+    /// let data = bdata!({"files" => [{"path" => ["a", "b"]}], "name" => "dir"});
+    /// let mut strings = 0;
+    /// let _ = data.walk(|path, v| {
+    ///     if path.last() == Some(&BPathSeg::Key(b"path")) {
+    ///         strings += v.as_list().unwrap().len();
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// });
+    /// assert_eq!(strings, 2);
+    /// ```
+    pub fn walk<F>(&self, mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(&[BPathSeg], &BData) -> ControlFlow<()>,
+    {
+        let mut path = Vec::new();
+        // 待访问的值, 以及其所在容器的路径长度与其在容器中的位置
+        let mut stack = vec![(0, None, self)];
+        while let Some((len, seg, data)) = stack.pop() {
+            path.truncate(len);
+            path.extend(seg);
+            f(&path, data)?;
+            let len = path.len();
+            match data {
+                BData::List(list) => {
+                    let items = list.iter().enumerate().rev();
+                    stack.extend(items.map(|(i, v)| (len, Some(BPathSeg::Index(i)), v)));
+                }
+                BData::Dict(dict) => {
+                    let entries = dict.iter().rev();
+                    stack.extend(entries.map(|(k, v)| (len, Some(BPathSeg::Key(k.as_slice())), v)));
+                }
+                _ => {}
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// 与 `walk` 相同, 但可以修改访问到的值.
+    ///
+    /// 容器在其中的元素之前访问, 因此 `f` 替换容器后访问的是替换后的元素
+    pub fn walk_mut<F>(&mut self, mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(&[BPathSeg], &mut BData) -> ControlFlow<()>,
+    {
+        let mut path = Vec::new();
+        let mut stack = vec![(0, None, self)];
+        while let Some((len, seg, data)) = stack.pop() {
+            path.truncate(len);
+            path.extend(seg);
+            f(&path, data)?;
+            let len = path.len();
+            match data {
+                BData::List(list) => {
+                    let items = list.iter_mut().enumerate().rev();
+                    stack.extend(items.map(|(i, v)| (len, Some(BPathSeg::Index(i)), v)));
+                }
+                BData::Dict(dict) => {
+                    let entries = dict.iter_mut().rev();
+                    stack.extend(entries.map(|(k, v)| (len, Some(BPathSeg::Key(k.as_slice())), v)));
+                }
+                _ => {}
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{BData, BPathSeg};
+    use std::ops::ControlFlow;
+
+    fn path_string(path: &[BPathSeg]) -> String {
+        let segs: Vec<String> = path
+            .iter()
+            .map(|seg| match seg {
+                BPathSeg::Key(k) => String::from_utf8_lossy(k).into_owned(),
+                BPathSeg::Index(i) => i.to_string(),
+            })
+            .collect();
+        segs.join("/")
+    }
+
+    fn fixture() -> BData {
+        bdata!({
+            "announce" => "http://t",
+            "info" => {
+                "files" => [{"length" => 3, "path" => ["a"]}, {"length" => 5}],
+                "name" => "dir",
+            },
+            "list" => [[], {}],
+        })
+    }
+
+    #[test]
+    fn walk_paths_test() {
+        let data = fixture();
+        let mut paths = Vec::new();
+        let res = data.walk(|path, v| {
+            assert_eq!(data.pointer(path), Some(v));
+            paths.push(path_string(path));
+            ControlFlow::Continue(())
+        });
+        assert_eq!(res, ControlFlow::Continue(()));
+        let expected = [
+            "",
+            "announce",
+            "info",
+            "info/files",
+            "info/files/0",
+            "info/files/0/length",
+            "info/files/0/path",
+            "info/files/0/path/0",
+            "info/files/1",
+            "info/files/1/length",
+            "info/name",
+            "list",
+            "list/0",
+            "list/1",
+        ];
+        assert_eq!(paths, expected);
+        assert_eq!(paths.len(), data.stats().node_count);
+    }
+
+    #[test]
+    fn walk_break_test() {
+        let data = fixture();
+        let mut count = 0;
+        let res = data.walk(|_, _| {
+            count += 1;
+            if count == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(res, ControlFlow::Break(()));
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn walk_mut_test() {
+        let mut data = fixture();
+        let res = data.walk_mut(|path, v| {
+            if let BData::Number(n) = v {
+                *n *= 10;
+            }
+            // 替换容器后继续访问新的元素
+            if path_string(path) == "list/1" {
+                *v = bdata!([1]);
+            }
+            ControlFlow::Continue(())
+        });
+        assert_eq!(res, ControlFlow::Continue(()));
+        let expected = bdata!({
+            "announce" => "http://t",
+            "info" => {
+                "files" => [{"length" => 30, "path" => ["a"]}, {"length" => 50}],
+                "name" => "dir",
+            },
+            "list" => [[], [10]],
+        });
+        assert_eq!(data, expected);
+
+        let mut n = 0;
+        let _ = data.walk_mut(|_, v| {
+            n += 1;
+            *v = bdata!(n);
+            ControlFlow::Break(())
+        });
+        assert_eq!(data, bdata!(1));
+    }
+
+    #[test]
+    fn walk_deep_test() {
+        let mut data = bdata!(1);
+        for _ in 0..100_000 {
+            data = BData::List(vec![data]);
+        }
+        let mut depth = 0;
+        let _ = data.walk(|path, _| {
+            depth = path.len();
+            ControlFlow::Continue(())
+        });
+        assert_eq!(depth, 100_000);
+    }
+}